@@ -2,11 +2,14 @@
 // Description: This file defines the Chain structure for the blockchain, 
 // including functions to manage blocks, validators, and consensus.
 
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use icn_shared::{Block, IcnError, IcnResult};
 use icn_consensus::Consensus;
 use rand::rngs::OsRng;
 use rand::Rng;
+use crate::genesis::is_genesis_block;
+use crate::transaction::Transaction;
 
 /// Represents a validator in the blockchain network.
 #[derive(Debug, Clone)]
@@ -105,6 +108,29 @@ pub struct Chain<C: Consensus> {
     pub consensus: Arc<RwLock<C>>,
     /// The list of active validators.
     pub validators: Vec<Validator>,
+    /// Maps transaction ids to their block index and position within the block.
+    tx_index: HashMap<String, (usize, usize)>,
+    /// The hash of the configured genesis block, once one has been set.
+    genesis_hash: Option<String>,
+}
+
+/// Parses the transactions of a block along with their positions.
+///
+/// The genesis block holds the genesis configuration rather than transactions, so it has
+/// none. Any other entry that is not a valid transaction is an error.
+fn block_transactions(block: &Block) -> IcnResult<Vec<(usize, Transaction)>> {
+    if is_genesis_block(block) {
+        return Ok(Vec::new());
+    }
+    block.transactions.iter().enumerate()
+        .map(|(position, tx)| {
+            serde_json::from_str::<Transaction>(tx)
+                .map(|transaction| (position, transaction))
+                .map_err(|e| IcnError::Blockchain(format!(
+                    "Malformed transaction at position {} of block {}: {}", position, block.index, e
+                )))
+        })
+        .collect()
 }
 
 impl<C: Consensus> Chain<C> {
@@ -122,6 +148,8 @@ impl<C: Consensus> Chain<C> {
             blocks: Vec::new(),
             consensus,
             validators: Vec::new(),
+            tx_index: HashMap::new(),
+            genesis_hash: None,
         }
     }
 
@@ -136,11 +164,17 @@ impl<C: Consensus> Chain<C> {
     /// * `IcnResult<()>` - Returns `Ok(())` if the block is successfully added,
     ///   or an `IcnError` if validation fails.
     pub fn add_block(&mut self, block: Block) -> IcnResult<()> {
+        let transactions = block_transactions(&block)?;
+        self.check_transactions(&block)?;
+
         let mut consensus = self.consensus.write().map_err(|_| {
             IcnError::Consensus("Failed to acquire write lock on consensus".to_string())
         })?;
         
         if consensus.validate(&block)? {
+            for (position, transaction) in transactions {
+                self.tx_index.insert(transaction.id, (self.blocks.len(), position));
+            }
             self.blocks.push(block);
             consensus.update_state(self)?;
             Ok(())
@@ -149,6 +183,97 @@ impl<C: Consensus> Chain<C> {
         }
     }
 
+    /// Sets the genesis block of the chain.
+    ///
+    /// On an empty chain the block is stored as the first block. On a chain that already
+    /// has blocks, such as one loaded from storage, the existing first block must match it.
+    ///
+    /// # Arguments
+    ///
+    /// * `genesis` - The genesis block created from the chain's configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the genesis block was stored or matches,
+    ///   or an `IcnError` if the chain has a different genesis block.
+    pub fn set_genesis(&mut self, genesis: Block) -> IcnResult<()> {
+        match self.blocks.first() {
+            Some(existing) if existing.hash != genesis.hash => {
+                return Err(IcnError::Blockchain(format!(
+                    "Genesis block {} does not match the configured genesis block {}",
+                    existing.hash, genesis.hash
+                )));
+            }
+            Some(_) => {}
+            // The genesis block holds the genesis configuration, so there is nothing to index
+            None => self.blocks.push(genesis.clone()),
+        }
+        self.genesis_hash = Some(genesis.hash);
+        Ok(())
+    }
+
+    /// Checks that a block contains only well-formed transactions that are not already in
+    /// the chain and that appear only once in the block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block whose transactions should be checked.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if all transactions are new, otherwise an error.
+    pub fn check_transactions(&self, block: &Block) -> IcnResult<()> {
+        let mut seen = HashSet::new();
+        for (_, transaction) in block_transactions(block)? {
+            if self.contains_transaction(&transaction.id) || !seen.insert(transaction.id.clone()) {
+                return Err(IcnError::Blockchain(format!("Transaction {} is already included", transaction.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up a transaction by its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(Block, Transaction)>` - The block containing the transaction and the
+    ///   transaction itself, or `None` if it is not in the chain.
+    pub fn get_transaction(&self, id: &str) -> Option<(Block, Transaction)> {
+        let (block_index, position) = *self.tx_index.get(id)?;
+        let block = self.blocks.get(block_index)?;
+        let transaction = serde_json::from_str(block.transactions.get(position)?).ok()?;
+        Some((block.clone(), transaction))
+    }
+
+    /// Returns whether a transaction with the given id is in the chain.
+    pub fn contains_transaction(&self, id: &str) -> bool {
+        self.tx_index.contains_key(id)
+    }
+
+    /// Rebuilds the transaction index from the blocks in the chain.
+    ///
+    /// This must be called after `blocks` is replaced directly, such as when loading a
+    /// persisted chain.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the index was rebuilt, or an `IcnError` if a
+    ///   block contains a malformed transaction. The index is left unchanged on error.
+    pub fn rebuild_transaction_index(&mut self) -> IcnResult<()> {
+        let mut tx_index = HashMap::new();
+        for (block_index, block) in self.blocks.iter().enumerate() {
+            for (position, transaction) in block_transactions(block)? {
+                tx_index.insert(transaction.id, (block_index, position));
+            }
+        }
+        self.tx_index = tx_index;
+        Ok(())
+    }
+
     /// Returns the latest block in the blockchain.
     ///
     /// # Returns
@@ -229,6 +354,13 @@ impl<C: Consensus> Chain<C> {
     ///
     /// * `bool` - Returns true if the blockchain is valid, otherwise false.
     pub fn is_valid(&self) -> bool {
+        if let Some(genesis_hash) = &self.genesis_hash {
+            match self.blocks.first() {
+                Some(genesis) if genesis.hash == *genesis_hash && genesis.is_valid() => {}
+                _ => return false,
+            }
+        }
+
         for i in 1..self.blocks.len() {
             let current_block = &self.blocks[i];
             let previous_block = &self.blocks[i - 1];
@@ -291,11 +423,35 @@ impl<C: Consensus> Chain<C> {
 mod tests {
     use super::*;
     use icn_consensus::ProofOfCooperation;
+    use crate::transaction::TransactionType;
 
     fn create_test_block() -> Block {
         Block::new(0, vec![], "genesis".to_string(), "test_proposer".to_string())
     }
 
+    fn create_test_transaction(id: &str) -> String {
+        serde_json::to_string(&Transaction::new(
+            id.to_string(),
+            TransactionType::Transfer {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 10,
+            },
+            None,
+            None,
+        )).unwrap()
+    }
+
+    fn create_indexed_chain() -> Chain<ProofOfCooperation> {
+        let consensus = Arc::new(RwLock::new(ProofOfCooperation::new()));
+        let mut chain = Chain::new(consensus);
+        let block0 = Block::new(0, vec![create_test_transaction("tx1")], "genesis".to_string(), "test_proposer".to_string());
+        let block1 = Block::new(1, vec![create_test_transaction("tx2"), create_test_transaction("tx3")], block0.hash.clone(), "test_proposer".to_string());
+        chain.blocks = vec![block0, block1];
+        chain.rebuild_transaction_index().unwrap();
+        chain
+    }
+
     #[test]
     fn test_chain_creation() {
         let consensus = Arc::new(RwLock::new(ProofOfCooperation::new()));
@@ -361,4 +517,60 @@ mod tests {
         assert!(selected_validators.is_ok());
         assert!(!selected_validators.unwrap().is_empty());
     }
+
+    #[test]
+    fn test_transaction_lookup() {
+        let chain = create_indexed_chain();
+
+        let (block, transaction) = chain.get_transaction("tx3").unwrap();
+        assert_eq!(block.index, 1);
+        assert_eq!(transaction.id, "tx3");
+        assert!(chain.contains_transaction("tx1"));
+        assert!(!chain.contains_transaction("tx4"));
+        assert!(chain.get_transaction("tx4").is_none());
+    }
+
+    #[test]
+    fn test_duplicate_transaction_rejected() {
+        let mut chain = create_indexed_chain();
+        let previous_hash = chain.latest_block().unwrap().hash.clone();
+
+        let duplicate = Block::new(2, vec![create_test_transaction("tx2")], previous_hash.clone(), "test_proposer".to_string());
+        match chain.add_block(duplicate) {
+            Err(IcnError::Blockchain(msg)) => assert!(msg.contains("tx2")),
+            other => panic!("Expected duplicate transaction error, got {:?}", other),
+        }
+
+        let repeated = Block::new(2, vec![create_test_transaction("tx4"), create_test_transaction("tx4")], previous_hash, "test_proposer".to_string());
+        assert!(chain.check_transactions(&repeated).is_err());
+        assert_eq!(chain.block_count(), 2);
+    }
+
+    #[test]
+    fn test_malformed_transaction_rejected() {
+        let mut chain = create_indexed_chain();
+        let previous_hash = chain.latest_block().unwrap().hash.clone();
+
+        let malformed = Block::new(2, vec![create_test_transaction("tx4"), "not a transaction".to_string()], previous_hash, "test_proposer".to_string());
+        match chain.add_block(malformed) {
+            Err(IcnError::Blockchain(msg)) => assert!(msg.contains("Malformed transaction at position 1")),
+            other => panic!("Expected malformed transaction error, got {:?}", other),
+        }
+        assert!(!chain.contains_transaction("tx4"));
+
+        chain.blocks[1].transactions.push("not a transaction".to_string());
+        assert!(chain.rebuild_transaction_index().is_err());
+        assert!(chain.contains_transaction("tx3"));
+    }
+
+    #[test]
+    fn test_transaction_index_rebuild() {
+        let mut chain = create_indexed_chain();
+        chain.blocks.truncate(1);
+        chain.rebuild_transaction_index().unwrap();
+
+        assert!(chain.contains_transaction("tx1"));
+        assert!(!chain.contains_transaction("tx2"));
+        assert!(chain.get_transaction("tx3").is_none());
+    }
 }
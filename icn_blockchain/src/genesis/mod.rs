@@ -0,0 +1,149 @@
+// File: icn_blockchain/src/genesis/mod.rs
+// Description: This file defines the genesis configuration of the blockchain,
+// including initial account balances and validators.
+
+use std::collections::{BTreeMap, HashSet};
+use serde::{Serialize, Deserialize};
+use icn_shared::{merkle_root, Block, IcnError, IcnResult};
+
+/// The proposer id recorded in the genesis block.
+const GENESIS_PROPOSER: &str = "genesis";
+
+/// A validator registered with the consensus mechanism at genesis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenesisValidator {
+    /// The unique identifier of the validator.
+    pub id: String,
+    /// The initial stake of the validator.
+    pub stake: u64,
+    /// The initial reputation of the validator, between 0 and 1.
+    pub reputation: f64,
+}
+
+/// Defines the initial state of a blockchain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    /// The identifier of the chain, so that chains with the same allocations differ.
+    pub chain_id: String,
+    /// The initial balances of accounts.
+    pub balances: BTreeMap<String, u64>,
+    /// The validators known at genesis.
+    pub validators: Vec<GenesisValidator>,
+}
+
+/// Returns whether a block is a genesis block created by `GenesisConfig::create_block`.
+///
+/// The genesis block carries the genesis configuration instead of transactions.
+pub(crate) fn is_genesis_block(block: &Block) -> bool {
+    block.index == 0 && block.proposer_id == GENESIS_PROPOSER
+}
+
+impl GenesisConfig {
+    /// Creates a new `GenesisConfig` with no balances or validators.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_id` - The identifier of the chain.
+    pub fn new(chain_id: String) -> Self {
+        GenesisConfig {
+            chain_id,
+            balances: BTreeMap::new(),
+            validators: Vec::new(),
+        }
+    }
+
+    /// Validates the genesis configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the configuration is valid, otherwise an error.
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.chain_id.is_empty() {
+            return Err(IcnError::Blockchain("Genesis chain id cannot be empty".to_string()));
+        }
+        if let Some((account, _)) = self.balances.iter().find(|(_, &balance)| i64::try_from(balance).is_err()) {
+            return Err(IcnError::Blockchain(format!("Genesis balance of account {} is too large", account)));
+        }
+
+        let mut seen = HashSet::new();
+        for validator in &self.validators {
+            if validator.id.is_empty() {
+                return Err(IcnError::Blockchain("Genesis validator id cannot be empty".to_string()));
+            }
+            if !seen.insert(&validator.id) {
+                return Err(IcnError::Blockchain(format!("Duplicate genesis validator {}", validator.id)));
+            }
+            if !(0.0..=1.0).contains(&validator.reputation) {
+                return Err(IcnError::Blockchain(format!("Genesis reputation of validator {} must be in [0, 1]", validator.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the genesis block described by this configuration.
+    ///
+    /// The block only depends on the configuration, so every node with the same
+    /// configuration creates a block with the same hash.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Block>` - The genesis block, or an error if the configuration is invalid.
+    pub fn create_block(&self) -> IcnResult<Block> {
+        self.validate()?;
+        let config = serde_json::to_string(self)
+            .map_err(|e| IcnError::Serialization(format!("Failed to serialize genesis config: {}", e)))?;
+
+        let mut block = Block {
+            index: 0,
+            timestamp: 0,
+            transactions: vec![config],
+            previous_hash: "0".repeat(64),
+            hash: String::new(),
+            proposer_id: GENESIS_PROPOSER.to_string(),
+            nonce: 0,
+            merkle_root: String::new(),
+        };
+        block.merkle_root = merkle_root(&block.transactions);
+        block.hash = block.calculate_hash();
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config() -> GenesisConfig {
+        let mut config = GenesisConfig::new("icn-test".to_string());
+        config.balances.insert("alice".to_string(), 1000);
+        config.balances.insert("bob".to_string(), 500);
+        config.validators.push(GenesisValidator { id: "validator1".to_string(), stake: 2000, reputation: 0.9 });
+        config
+    }
+
+    #[test]
+    fn test_genesis_block_is_deterministic() {
+        let block_a = create_test_config().create_block().unwrap();
+        let block_b = create_test_config().create_block().unwrap();
+        assert_eq!(block_a, block_b);
+        assert!(block_a.is_valid());
+        assert!(is_genesis_block(&block_a));
+
+        let mut other_chain = create_test_config();
+        other_chain.chain_id = "icn-other".to_string();
+        assert_ne!(other_chain.create_block().unwrap().hash, block_a.hash);
+    }
+
+    #[test]
+    fn test_invalid_genesis_config() {
+        let mut config = create_test_config();
+        config.validators.push(config.validators[0].clone());
+        assert!(config.create_block().is_err());
+
+        let mut config = create_test_config();
+        config.validators[0].reputation = 1.5;
+        assert!(config.validate().is_err());
+
+        assert!(GenesisConfig::new(String::new()).validate().is_err());
+    }
+}
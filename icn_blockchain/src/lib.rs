@@ -1,22 +1,31 @@
 // File: icn_blockchain/src/lib.rs
 
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
-use icn_shared::{Block, IcnError, IcnResult};
+use icn_shared::{IcnError, IcnResult};
+use sha2::{Digest, Sha256};
 use icn_consensus::Consensus;
 use icn_virtual_machine::VirtualMachine;
 
 pub mod chain;
+pub mod genesis;
 pub mod transaction;
 
 use crate::chain::Chain;
+use crate::genesis::GenesisConfig;
 use crate::transaction::{Transaction, TransactionType};
 
+pub use icn_shared::Block;
+
 /// Represents the blockchain and its operations.
 pub struct Blockchain<C: Consensus> {
     pub chain: Chain<C>,
     pub consensus: Arc<RwLock<C>>,
     pub vm: VirtualMachine,
-    state: RwLock<std::collections::HashMap<String, i64>>,
+    state: RwLock<HashMap<String, i64>>,
+    /// Undo logs of the blocks being executed, mapping each changed account to its
+    /// balance before the block (`None` if the account did not exist).
+    undo_logs: RwLock<Vec<HashMap<String, Option<i64>>>>,
 }
 
 impl<C: Consensus> Blockchain<C> {
@@ -26,8 +35,41 @@ impl<C: Consensus> Blockchain<C> {
             chain: Chain::new(consensus.clone()),
             consensus,
             vm: VirtualMachine::new(),
-            state: RwLock::new(std::collections::HashMap::new()),
+            state: RwLock::new(HashMap::new()),
+            undo_logs: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Initializes the blockchain from its genesis configuration.
+    ///
+    /// On an empty blockchain this stores the genesis block, seeds the initial balances and
+    /// registers the genesis validators with the consensus mechanism. On a blockchain that
+    /// already has blocks it only checks that the existing genesis block matches.
+    pub fn initialize_genesis(&mut self, genesis: GenesisConfig) -> IcnResult<()> {
+        // Creating the block validates the whole configuration, so nothing is
+        // applied until every validator and balance has been checked
+        let genesis_block = genesis.create_block()?;
+        if self.chain.block_count() > 0 {
+            return self.chain.set_genesis(genesis_block);
+        }
+
+        let mut consensus = self.consensus.write()
+            .map_err(|_| IcnError::Consensus("Failed to acquire write lock on consensus".to_string()))?;
+        let mut state = self.state.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on state".to_string()))?;
+
+        for validator in &genesis.validators {
+            consensus.register_validator(&validator.id, validator.stake, validator.reputation)?;
         }
+        for (account, balance) in &genesis.balances {
+            // Balances were checked to fit in an i64 when the block was created
+            state.insert(account.clone(), *balance as i64);
+        }
+        drop(state);
+
+        self.chain.set_genesis(genesis_block.clone())?;
+        consensus.initialize(&genesis_block)?;
+        Ok(())
     }
 
     /// Adds a new block to the blockchain after validating it.
@@ -48,15 +90,24 @@ impl<C: Consensus> Blockchain<C> {
         if consensus.validate(&new_block)? {
             drop(consensus); // Release the read lock before acquiring the write lock
             
-            // Execute all transactions in the block
-            for tx in &new_block.transactions {
-                let transaction: Transaction = serde_json::from_str(tx)
-                    .map_err(|e| IcnError::Blockchain(format!("Failed to deserialize transaction: {}", e)))?;
-                self.execute_transaction(transaction)?;
-            }
+            let transactions = new_block.transactions.iter()
+                .map(|tx| serde_json::from_str::<Transaction>(tx)
+                    .map_err(|e| IcnError::Blockchain(format!("Failed to deserialize transaction: {}", e))))
+                .collect::<IcnResult<Vec<_>>>()?;
+
+            // Reject already-included transactions before any of them are executed
+            self.chain.check_transactions(&new_block)?;
 
-            // Add the block to the chain
-            self.chain.add_block(new_block.clone())?;
+            // Execute all transactions in the block, leaving the state untouched if any fails
+            self.begin_block()?;
+            let result = transactions.into_iter()
+                .try_for_each(|transaction| self.execute_transaction(transaction))
+                .and_then(|_| self.chain.add_block(new_block.clone()));
+            if let Err(e) = result {
+                self.rollback_block()?;
+                return Err(e);
+            }
+            self.commit_block()?;
             
             // Update the consensus state
             let mut consensus = self.consensus.write()
@@ -73,9 +124,9 @@ impl<C: Consensus> Blockchain<C> {
     pub fn execute_transaction(&self, transaction: Transaction) -> IcnResult<()> {
         match &transaction.transaction_type {
             TransactionType::Transfer { from, to, amount } => {
-                self.update_balance(from, -(*amount as i64))?;
-                self.update_balance(to, *amount as i64)?;
-                Ok(())
+                let amount = i64::try_from(*amount)
+                    .map_err(|_| IcnError::Blockchain(format!("Transfer amount {} is too large", amount)))?;
+                self.update_balances(&[(from.as_str(), -amount), (to.as_str(), amount)])
             }
             TransactionType::DeployContract { code, .. } => {
                 let bytecode = self.vm.compile_contract(code)?;
@@ -93,14 +144,86 @@ impl<C: Consensus> Blockchain<C> {
         }
     }
 
-    /// Updates the balance of an account.
-    fn update_balance(&self, account: &str, change: i64) -> IcnResult<()> {
+    /// Starts recording balance changes so that they can be undone with `rollback_block`.
+    ///
+    /// Blocks can be nested; committing an inner block keeps its changes undoable by the outer one.
+    pub fn begin_block(&self) -> IcnResult<()> {
+        let mut undo_logs = self.undo_logs.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on undo logs".to_string()))?;
+        undo_logs.push(HashMap::new());
+        Ok(())
+    }
+
+    /// Keeps the balance changes made since the matching `begin_block`.
+    pub fn commit_block(&self) -> IcnResult<()> {
+        let mut undo_logs = self.undo_logs.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on undo logs".to_string()))?;
+        let undo_log = undo_logs.pop()
+            .ok_or_else(|| IcnError::Blockchain("No block in progress".to_string()))?;
+        if let Some(parent) = undo_logs.last_mut() {
+            for (account, previous) in undo_log {
+                parent.entry(account).or_insert(previous);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores the balances to what they were at the matching `begin_block`.
+    pub fn rollback_block(&self) -> IcnResult<()> {
+        let undo_log = self.undo_logs.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on undo logs".to_string()))?
+            .pop()
+            .ok_or_else(|| IcnError::Blockchain("No block in progress".to_string()))?;
         let mut state = self.state.write()
             .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on state".to_string()))?;
-        let balance = state.entry(account.to_string()).or_insert(0);
-        *balance += change;
-        if *balance < 0 {
-            return Err(IcnError::Blockchain(format!("Insufficient balance for account {}", account)));
+        for (account, previous) in undo_log {
+            match previous {
+                Some(balance) => state.insert(account, balance),
+                None => state.remove(&account),
+            };
+        }
+        Ok(())
+    }
+
+    /// Calculates a deterministic hash of all account balances.
+    pub fn state_root(&self) -> IcnResult<String> {
+        let state = self.state.read()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire read lock on state".to_string()))?;
+        let sorted: BTreeMap<&String, &i64> = state.iter().collect();
+        let encoded = serde_json::to_vec(&sorted)
+            .map_err(|e| IcnError::Serialization(format!("Failed to serialize state: {}", e)))?;
+        Ok(format!("{:x}", Sha256::digest(&encoded)))
+    }
+
+    /// Applies a set of balance changes in order, either all or none of them.
+    ///
+    /// Fails without changing any balance if a change would make an account negative.
+    fn update_balances(&self, changes: &[(&str, i64)]) -> IcnResult<()> {
+        let mut state = self.state.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on state".to_string()))?;
+
+        let mut new_balances: HashMap<&str, i64> = HashMap::new();
+        for &(account, change) in changes {
+            let balance = new_balances.get(account).copied()
+                .unwrap_or_else(|| state.get(account).copied().unwrap_or(0));
+            let new_balance = balance.checked_add(change)
+                .ok_or_else(|| IcnError::Blockchain(format!("Balance overflow for account {}", account)))?;
+            if new_balance < 0 {
+                return Err(IcnError::Blockchain(format!("Insufficient balance for account {}", account)));
+            }
+            new_balances.insert(account, new_balance);
+        }
+
+        if let Some(undo_log) = self.undo_logs.write()
+            .map_err(|_| IcnError::Blockchain("Failed to acquire write lock on undo logs".to_string()))?
+            .last_mut()
+        {
+            for account in new_balances.keys() {
+                undo_log.entry(account.to_string()).or_insert_with(|| state.get(*account).copied());
+            }
+        }
+        for (account, balance) in new_balances {
+            state.insert(account.to_string(), balance);
         }
         Ok(())
     }
@@ -130,6 +253,16 @@ impl<C: Consensus> Blockchain<C> {
         self.chain.latest_block()
     }
 
+    /// Looks up a transaction by its id, returning it along with the block that contains it.
+    pub fn get_transaction(&self, id: &str) -> Option<(Block, Transaction)> {
+        self.chain.get_transaction(id)
+    }
+
+    /// Checks whether a transaction with the given id is already in the blockchain.
+    pub fn contains_transaction(&self, id: &str) -> bool {
+        self.chain.contains_transaction(id)
+    }
+
     /// Gets the balance of an account.
     pub fn get_balance(&self, account: &str) -> IcnResult<i64> {
         let state = self.state.read()
@@ -180,6 +313,7 @@ mod tests {
     #[test]
     fn test_execute_transaction() {
         let blockchain = setup_blockchain();
+        blockchain.update_balances(&[("from_account", 100)]).unwrap();
         let transaction = Transaction::new(
             "1".to_string(),
             TransactionType::Transfer {
@@ -191,7 +325,7 @@ mod tests {
             None,
         );
         assert!(blockchain.execute_transaction(transaction).is_ok());
-        assert_eq!(blockchain.get_balance("from_account").unwrap(), -100);
+        assert_eq!(blockchain.get_balance("from_account").unwrap(), 0);
         assert_eq!(blockchain.get_balance("to_account").unwrap(), 100);
     }
 
@@ -315,4 +449,125 @@ mod tests {
         
         assert!(!blockchain.is_valid_chain());
     }
-}
\ No newline at end of file
+
+    fn create_genesis_config() -> GenesisConfig {
+        let mut genesis = GenesisConfig::new("icn-test".to_string());
+        genesis.balances.insert("alice".to_string(), 1000);
+        genesis.balances.insert("bob".to_string(), 250);
+        for id in ["validator1", "validator2", "validator3"] {
+            genesis.validators.push(crate::genesis::GenesisValidator { id: id.to_string(), stake: 2000, reputation: 0.9 });
+        }
+        genesis
+    }
+
+    #[test]
+    fn test_genesis_seeds_balances_and_validators() {
+        let mut blockchain = setup_blockchain();
+        blockchain.initialize_genesis(create_genesis_config()).unwrap();
+
+        assert_eq!(blockchain.block_count(), 1);
+        assert_eq!(blockchain.latest_block().unwrap().hash, create_genesis_config().create_block().unwrap().hash);
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 1000);
+        assert_eq!(blockchain.get_balance("bob").unwrap(), 250);
+        assert!(blockchain.is_valid_chain());
+
+        let mut validators = blockchain.consensus.read().unwrap().current_validators().unwrap();
+        validators.sort();
+        assert_eq!(validators, vec!["validator1", "validator2", "validator3"]);
+    }
+
+    #[test]
+    fn test_genesis_mismatch() {
+        let mut blockchain = setup_blockchain();
+        blockchain.initialize_genesis(create_genesis_config()).unwrap();
+        assert!(blockchain.initialize_genesis(create_genesis_config()).is_ok());
+
+        let mut other = create_genesis_config();
+        other.chain_id = "icn-other".to_string();
+        assert!(blockchain.initialize_genesis(other).is_err());
+
+        blockchain.chain.blocks[0].transactions.clear();
+        assert!(!blockchain.is_valid_chain());
+    }
+
+    fn create_transfer(id: &str, from: &str, to: &str, amount: u64) -> Transaction {
+        Transaction::new(
+            id.to_string(),
+            TransactionType::Transfer {
+                from: from.to_string(),
+                to: to.to_string(),
+                amount,
+            },
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_rollback_after_failed_transaction() {
+        let mut blockchain = setup_blockchain();
+        blockchain.initialize_genesis(create_genesis_config()).unwrap();
+        let state_root = blockchain.state_root().unwrap();
+
+        blockchain.begin_block().unwrap();
+        assert!(blockchain.execute_transaction(create_transfer("1", "alice", "carol", 300)).is_ok());
+        assert!(blockchain.execute_transaction(create_transfer("2", "bob", "carol", 500)).is_err());
+        blockchain.rollback_block().unwrap();
+
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 1000);
+        assert_eq!(blockchain.get_balance("bob").unwrap(), 250);
+        assert!(blockchain.get_balance("carol").is_err());
+        assert_eq!(blockchain.state_root().unwrap(), state_root);
+    }
+
+    #[test]
+    fn test_multi_block_rollback() {
+        let mut blockchain = setup_blockchain();
+        blockchain.initialize_genesis(create_genesis_config()).unwrap();
+        let state_root = blockchain.state_root().unwrap();
+
+        blockchain.begin_block().unwrap();
+        blockchain.execute_transaction(create_transfer("1", "alice", "bob", 100)).unwrap();
+        blockchain.begin_block().unwrap();
+        blockchain.execute_transaction(create_transfer("2", "bob", "carol", 200)).unwrap();
+        blockchain.commit_block().unwrap();
+        assert_eq!(blockchain.get_balance("carol").unwrap(), 200);
+        assert_ne!(blockchain.state_root().unwrap(), state_root);
+
+        blockchain.rollback_block().unwrap();
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 1000);
+        assert_eq!(blockchain.get_balance("bob").unwrap(), 250);
+        assert!(blockchain.get_balance("carol").is_err());
+        assert_eq!(blockchain.state_root().unwrap(), state_root);
+        assert!(blockchain.rollback_block().is_err());
+    }
+
+    #[test]
+    fn test_failed_transfer_leaves_balances_untouched() {
+        let blockchain = setup_blockchain();
+        blockchain.update_balances(&[("alice", 50)]).unwrap();
+
+        assert!(blockchain.execute_transaction(create_transfer("1", "alice", "bob", 100)).is_err());
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 50);
+        assert!(blockchain.get_balance("bob").is_err());
+        assert!(blockchain.update_balances(&[("alice", -51)]).is_err());
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 50);
+    }
+
+    #[test]
+    fn test_transfer_order_within_block() {
+        // bob can only pass funds on after receiving them earlier in the block
+        let blockchain = setup_blockchain();
+        blockchain.update_balances(&[("alice", 100)]).unwrap();
+        blockchain.execute_transaction(create_transfer("1", "alice", "bob", 100)).unwrap();
+        blockchain.execute_transaction(create_transfer("2", "bob", "carol", 100)).unwrap();
+        assert_eq!(blockchain.get_balance("bob").unwrap(), 0);
+        assert_eq!(blockchain.get_balance("carol").unwrap(), 100);
+
+        let blockchain = setup_blockchain();
+        blockchain.update_balances(&[("alice", 100)]).unwrap();
+        assert!(blockchain.execute_transaction(create_transfer("2", "bob", "carol", 100)).is_err());
+        assert_eq!(blockchain.get_balance("alice").unwrap(), 100);
+        assert!(blockchain.get_balance("carol").is_err());
+    }
+}
@@ -93,6 +93,24 @@ pub trait Consensus: Clone + Send + Sync {
     ///   or an `IcnError` if it fails.
     fn initialize(&mut self, latest_block: &Block) -> IcnResult<()>;
 
+    /// Registers a validator with an initial stake and reputation.
+    ///
+    /// This method is used when bootstrapping a chain from its genesis configuration,
+    /// before any blocks have been validated. Registering a validator that is already
+    /// known replaces its stake and reputation.
+    ///
+    /// # Arguments
+    ///
+    /// * `validator_id` - The ID of the validator.
+    /// * `stake` - The validator's initial stake.
+    /// * `reputation` - The validator's initial reputation, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the validator is registered,
+    ///   or an `IcnError` if registration fails.
+    fn register_validator(&self, validator_id: &str, stake: u64, reputation: f64) -> IcnResult<()>;
+
     /// Handles network events that may affect the consensus state.
     ///
     /// This method allows the consensus mechanism to react to various network
@@ -1,14 +1,15 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Write};
 use std::sync::{Arc, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 use icn_shared::{Block, IcnError, IcnResult};
 use log::{info, warn, error};
 use serde::{Serialize, Deserialize};
-use rand::Rng;
 use crate::consensus::Consensus;
 
 /// Constants for Proof of Cooperation consensus mechanism
 const DEFAULT_REPUTATION_DECAY: f64 = 0.95;  // Decay factor for inactive members
+const REPUTATION_SMOOTHING: f64 = 0.95;  // Weight of a new reputation sample over the previous score
 const WEIGHT_CONSISTENCY: f64 = 0.3;
 const WEIGHT_QUALITY: f64 = 0.4;
 const WEIGHT_IMPACT: f64 = 0.3;
@@ -19,6 +20,12 @@ const MIN_GOV_PARTICIPATION: u64 = 10;
 const DEFAULT_BLOCK_TIME: u64 = 10;  // Minimum block interval
 const MAX_VALIDATOR_COUNT: usize = 10;
 const COOP_SCORE_WINDOW: usize = 50;  // Cooperation score window
+const DEFAULT_REPUTATION_DECAY_EPOCH: u64 = 86_400;  // Seconds of inactivity per decay step
+const DEFAULT_REPUTATION_FLOOR: f64 = 0.1;  // Decay never takes reputation below this
+const DEFAULT_EPOCH_LENGTH: u64 = 100;  // Blocks per validator epoch
+const STATE_VERSION: u32 = 1;  // Version tag written by save_state
+const DEFAULT_MAX_BLOCK_TRANSACTIONS: usize = 1_000;  // Transactions per block
+const DEFAULT_MAX_BLOCK_SIZE: usize = 1_048_576;  // Serialized block size in bytes
 
 /// Struct representing a peer's stake information
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -56,6 +63,38 @@ struct GovernanceParticipation {
     last_update: u64,
 }
 
+/// Struct tracking when a peer was last active and how far its reputation has decayed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ActivityRecord {
+    last_active: u64,
+    decayed_until: u64,
+}
+
+/// Struct holding the validator set selected for an epoch
+#[derive(Clone, Debug, Default)]
+struct ValidatorSet {
+    epoch: u64,
+    /// The validators with their stake-weighted scores, taken when the set was selected
+    validators: Option<Vec<(String, f64)>>,
+}
+
+/// Struct holding the persisted per-peer state of the consensus mechanism
+#[derive(Debug, Serialize, Deserialize)]
+struct ConsensusState {
+    version: u32,
+    known_peers: HashSet<String>,
+    cooperation_scores: HashMap<String, VecDeque<f64>>,
+    reputation_scores: HashMap<String, f64>,
+    contribution_history: HashMap<String, VecDeque<(u64, f64)>>,
+    stake_info: HashMap<String, StakeInfo>,
+    computational_power: HashMap<String, ComputationalPower>,
+    storage_provision: HashMap<String, StorageProvision>,
+    governance_participation: HashMap<String, GovernanceParticipation>,
+    activity: HashMap<String, ActivityRecord>,
+    last_block_time: u64,
+    last_block_hash: String,
+}
+
 /// The main struct implementing the Proof of Cooperation consensus mechanism
 #[derive(Clone)]
 pub struct ProofOfCooperation {
@@ -68,6 +107,15 @@ pub struct ProofOfCooperation {
     storage_provision: Arc<RwLock<HashMap<String, StorageProvision>>>,
     governance_participation: Arc<RwLock<HashMap<String, GovernanceParticipation>>>,
     last_block_time: Arc<RwLock<u64>>,
+    last_block_hash: Arc<RwLock<String>>,
+    activity: Arc<RwLock<HashMap<String, ActivityRecord>>>,
+    reputation_decay: f64,
+    reputation_floor: f64,
+    reputation_decay_epoch: u64,
+    validator_set: Arc<RwLock<ValidatorSet>>,
+    epoch_length: u64,
+    max_block_transactions: usize,
+    max_block_size: usize,
 }
 
 impl ProofOfCooperation {
@@ -83,6 +131,15 @@ impl ProofOfCooperation {
             storage_provision: Arc::new(RwLock::new(HashMap::new())),
             governance_participation: Arc::new(RwLock::new(HashMap::new())),
             last_block_time: Arc::new(RwLock::new(0)),
+            last_block_hash: Arc::new(RwLock::new(String::new())),
+            activity: Arc::new(RwLock::new(HashMap::new())),
+            reputation_decay: DEFAULT_REPUTATION_DECAY,
+            reputation_floor: DEFAULT_REPUTATION_FLOOR,
+            reputation_decay_epoch: DEFAULT_REPUTATION_DECAY_EPOCH,
+            validator_set: Arc::new(RwLock::new(ValidatorSet::default())),
+            epoch_length: DEFAULT_EPOCH_LENGTH,
+            max_block_transactions: DEFAULT_MAX_BLOCK_TRANSACTIONS,
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
         }
     }
 
@@ -105,19 +162,19 @@ impl ProofOfCooperation {
         Ok(())
     }
 
-    /// Helper function to initialize data for a new peer
+    /// Helper function to initialize data for a new peer, keeping any data it already has
     fn initialize_peer_data(&self, peer_id: &str, current_time: u64) -> IcnResult<()> {
         self.cooperation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for cooperation_scores".to_string()))?
-            .insert(peer_id.to_string(), VecDeque::from(vec![1.0; COOP_SCORE_WINDOW]));
+            .entry(peer_id.to_string()).or_insert(VecDeque::from(vec![1.0; COOP_SCORE_WINDOW]));
 
         self.reputation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for reputation_scores".to_string()))?
-            .insert(peer_id.to_string(), 1.0);
+            .entry(peer_id.to_string()).or_insert(1.0);
 
         self.contribution_history.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for contribution_history".to_string()))?
-            .insert(peer_id.to_string(), VecDeque::new());
+            .entry(peer_id.to_string()).or_default();
 
         self.stake_info.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for stake_info".to_string()))?
-            .insert(peer_id.to_string(), StakeInfo {
+            .entry(peer_id.to_string()).or_insert(StakeInfo {
                 amount: 0,
                 asset_type: "ICN".to_string(),
                 duration: 0,
@@ -125,7 +182,7 @@ impl ProofOfCooperation {
             });
 
         self.computational_power.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for computational_power".to_string()))?
-            .insert(peer_id.to_string(), ComputationalPower {
+            .entry(peer_id.to_string()).or_insert(ComputationalPower {
                 cpu_power: 0,
                 gpu_power: 0,
                 specialized_hardware: Vec::new(),
@@ -133,7 +190,7 @@ impl ProofOfCooperation {
             });
 
         self.storage_provision.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for storage_provision".to_string()))?
-            .insert(peer_id.to_string(), StorageProvision {
+            .entry(peer_id.to_string()).or_insert(StorageProvision {
                 capacity: 0,
                 reliability: 1.0,
                 uptime: 1.0,
@@ -141,46 +198,281 @@ impl ProofOfCooperation {
             });
 
         self.governance_participation.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for governance_participation".to_string()))?
-            .insert(peer_id.to_string(), GovernanceParticipation {
+            .entry(peer_id.to_string()).or_insert(GovernanceParticipation {
                 proposals_submitted: 0,
                 votes_cast: 0,
                 discussions_participated: 0,
                 last_update: current_time,
             });
 
+        self.activity.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for activity".to_string()))?
+            .entry(peer_id.to_string()).or_insert(ActivityRecord {
+                last_active: current_time,
+                decayed_until: current_time,
+            });
+
         Ok(())
     }
 
-    /// Selects validators for block validation
-    fn select_validators(&self) -> IcnResult<Vec<String>> {
-        let known_peers = self.known_peers.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for known_peers".to_string()))?;
-        let stake_info = self.stake_info.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for stake_info".to_string()))?;
+    /// Writes the per-peer consensus state to `writer` as versioned JSON
+    pub fn save_state<W: Write>(&self, writer: W) -> IcnResult<()> {
+        let state = ConsensusState {
+            version: STATE_VERSION,
+            known_peers: self.known_peers.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for known_peers".to_string()))?.clone(),
+            cooperation_scores: self.cooperation_scores.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for cooperation_scores".to_string()))?.clone(),
+            reputation_scores: self.reputation_scores.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for reputation_scores".to_string()))?.clone(),
+            contribution_history: self.contribution_history.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for contribution_history".to_string()))?.clone(),
+            stake_info: self.stake_info.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for stake_info".to_string()))?.clone(),
+            computational_power: self.computational_power.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for computational_power".to_string()))?.clone(),
+            storage_provision: self.storage_provision.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for storage_provision".to_string()))?.clone(),
+            governance_participation: self.governance_participation.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for governance_participation".to_string()))?.clone(),
+            activity: self.activity.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for activity".to_string()))?.clone(),
+            last_block_time: *self.last_block_time.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for last_block_time".to_string()))?,
+            last_block_hash: self.last_block_hash.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for last_block_hash".to_string()))?.clone(),
+        };
+        serde_json::to_writer(writer, &state)
+            .map_err(|e| IcnError::Serialization(format!("Failed to write consensus state: {}", e)))
+    }
+
+    /// Replaces the per-peer consensus state with a snapshot written by `save_state`
+    ///
+    /// Data for peers missing from the snapshot's peer list is dropped, and known peers
+    /// without data in the snapshot are given the defaults of a newly registered peer.
+    /// The validator set is selected again at the next epoch boundary.
+    pub fn load_state<R: Read>(&self, reader: R) -> IcnResult<()> {
+        let mut state: ConsensusState = serde_json::from_reader(reader)
+            .map_err(|e| IcnError::Serialization(format!("Failed to read consensus state: {}", e)))?;
+        if state.version != STATE_VERSION {
+            return Err(IcnError::Consensus(format!("Unsupported consensus state version: {}", state.version)));
+        }
+
+        let known_peers = state.known_peers.clone();
+        let is_known = |peer_id: &String| known_peers.contains(peer_id);
+        let before = state.stake_info.len() + state.reputation_scores.len();
+        state.cooperation_scores.retain(|peer_id, _| is_known(peer_id));
+        state.reputation_scores.retain(|peer_id, _| is_known(peer_id));
+        state.contribution_history.retain(|peer_id, _| is_known(peer_id));
+        state.stake_info.retain(|peer_id, _| is_known(peer_id));
+        state.computational_power.retain(|peer_id, _| is_known(peer_id));
+        state.storage_provision.retain(|peer_id, _| is_known(peer_id));
+        state.governance_participation.retain(|peer_id, _| is_known(peer_id));
+        state.activity.retain(|peer_id, _| is_known(peer_id));
+        if state.stake_info.len() + state.reputation_scores.len() < before {
+            warn!("Dropped consensus state of peers missing from the snapshot's peer list");
+        }
+
+        *self.known_peers.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for known_peers".to_string()))? = state.known_peers;
+        *self.cooperation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for cooperation_scores".to_string()))? = state.cooperation_scores;
+        *self.reputation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for reputation_scores".to_string()))? = state.reputation_scores;
+        *self.contribution_history.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for contribution_history".to_string()))? = state.contribution_history;
+        *self.stake_info.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for stake_info".to_string()))? = state.stake_info;
+        *self.computational_power.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for computational_power".to_string()))? = state.computational_power;
+        *self.storage_provision.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for storage_provision".to_string()))? = state.storage_provision;
+        *self.governance_participation.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for governance_participation".to_string()))? = state.governance_participation;
+        *self.activity.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for activity".to_string()))? = state.activity;
+        *self.last_block_time.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_time".to_string()))? = state.last_block_time;
+        *self.last_block_hash.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_hash".to_string()))? = state.last_block_hash;
+        *self.validator_set.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for validator_set".to_string()))? = ValidatorSet::default();
+
+        let current_time = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_err(|e| IcnError::Consensus(format!("System time error: {}", e)))?
+            .as_secs();
+        for peer_id in &known_peers {
+            self.initialize_peer_data(peer_id, current_time)?;
+        }
+
+        info!("Loaded consensus state for {} peers", known_peers.len());
+        Ok(())
+    }
+
+    /// Sets how quickly the reputation of inactive peers decays
+    ///
+    /// Each full epoch of inactivity multiplies a peer's reputation by `factor`, but never
+    /// takes it below `floor`.
+    pub fn set_reputation_decay(&mut self, factor: f64, floor: f64) -> IcnResult<()> {
+        if !(factor > 0.0 && factor <= 1.0) {
+            return Err(IcnError::Consensus(format!("Reputation decay factor must be in (0, 1], got {}", factor)));
+        }
+        if !(0.0..=1.0).contains(&floor) {
+            return Err(IcnError::Consensus(format!("Reputation floor must be in [0, 1], got {}", floor)));
+        }
+        self.reputation_decay = factor;
+        self.reputation_floor = floor;
+        Ok(())
+    }
+
+    /// Sets the number of seconds of inactivity that make up one reputation decay step
+    pub fn set_reputation_decay_epoch(&mut self, seconds: u64) -> IcnResult<()> {
+        if seconds == 0 {
+            return Err(IcnError::Consensus("Reputation decay epoch must be at least one second".to_string()));
+        }
+        self.reputation_decay_epoch = seconds;
+        Ok(())
+    }
+
+    /// Returns the reputation of a peer, or an error if the peer is not known
+    pub fn get_reputation(&self, peer_id: &str) -> IcnResult<f64> {
         let reputation_scores = self.reputation_scores.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for reputation_scores".to_string()))?;
+        reputation_scores.get(peer_id).copied()
+            .ok_or_else(|| IcnError::Consensus(format!("Unknown peer: {}", peer_id)))
+    }
 
-        let validators: Vec<String> = known_peers
-            .iter()
-            .filter(|&peer_id| {
-                let stake = stake_info.get(peer_id).map(|info| info.amount).unwrap_or(0);
-                let reputation = reputation_scores.get(peer_id).cloned().unwrap_or(0.0);
-                stake > MIN_STAKE_SYBIL && reputation > MIN_REPUTATION_SYBIL
-            })
-            .cloned()
-            .collect();
+    /// Records that a peer took part in consensus at the given time
+    fn record_activity(&self, peer_id: &str, timestamp: u64) -> IcnResult<()> {
+        let mut activity = self.activity.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for activity".to_string()))?;
+        if let Some(record) = activity.get_mut(peer_id) {
+            record.last_active = record.last_active.max(timestamp);
+        }
+        Ok(())
+    }
+
+    /// Decays the reputation of peers for every full epoch they have been inactive
+    ///
+    /// Epochs already accounted for by an earlier call are not decayed again, so this can
+    /// be called as often as needed.
+    pub fn apply_reputation_decay(&self, now: u64) -> IcnResult<()> {
+        let mut activity = self.activity.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for activity".to_string()))?;
+        let mut rep_scores = self.reputation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for reputation_scores".to_string()))?;
+
+        for (peer_id, record) in activity.iter_mut() {
+            let since = record.last_active.max(record.decayed_until);
+            let epochs = now.saturating_sub(since) / self.reputation_decay_epoch;
+            if epochs == 0 {
+                continue;
+            }
+            record.decayed_until = since + epochs * self.reputation_decay_epoch;
 
-        if validators.len() < 3 {
+            if let Some(rep_score) = rep_scores.get_mut(peer_id) {
+                if *rep_score > self.reputation_floor {
+                    let decay = self.reputation_decay.powi(epochs.min(i32::MAX as u64) as i32);
+                    *rep_score = (*rep_score * decay).max(self.reputation_floor);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects validators for block validation, together with their current scores
+    fn select_validators(&self) -> IcnResult<Vec<(String, f64)>> {
+        let candidates: Vec<String> = {
+            let known_peers = self.known_peers.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for known_peers".to_string()))?;
+            let stake_info = self.stake_info.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for stake_info".to_string()))?;
+            let reputation_scores = self.reputation_scores.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for reputation_scores".to_string()))?;
+
+            known_peers
+                .iter()
+                .filter(|&peer_id| {
+                    let stake = stake_info.get(peer_id).map(|info| info.amount).unwrap_or(0);
+                    let reputation = reputation_scores.get(peer_id).cloned().unwrap_or(0.0);
+                    stake > MIN_STAKE_SYBIL && reputation > MIN_REPUTATION_SYBIL
+                })
+                .cloned()
+                .collect()
+        };
+
+        if candidates.len() < 3 {
             return Err(IcnError::Consensus("Not enough eligible validators".to_string()));
         }
 
-        let mut sorted_validators = validators;
-        sorted_validators.sort_by(|a, b| {
-            let a_score = self.calculate_validator_score(a);
-            let b_score = self.calculate_validator_score(b);
-            b_score.partial_cmp(&a_score).unwrap()
-        });
+        let mut sorted_validators: Vec<(String, f64)> = candidates
+            .into_iter()
+            .map(|peer_id| {
+                let score = self.calculate_validator_score(&peer_id);
+                (peer_id, score)
+            })
+            .collect();
+        sorted_validators.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
 
         Ok(sorted_validators.into_iter().take(MAX_VALIDATOR_COUNT).collect())
     }
 
+    /// Sets the number of blocks in a validator epoch
+    pub fn set_epoch_length(&mut self, blocks: u64) -> IcnResult<()> {
+        if blocks == 0 {
+            return Err(IcnError::Consensus("Epoch length must be at least one block".to_string()));
+        }
+        self.epoch_length = blocks;
+        Ok(())
+    }
+
+    /// Sets the largest number of transactions and serialized size in bytes a block may have
+    pub fn set_block_limits(&mut self, max_transactions: usize, max_size: usize) -> IcnResult<()> {
+        if max_transactions == 0 || max_size == 0 {
+            return Err(IcnError::Consensus("Block limits must be greater than zero".to_string()));
+        }
+        self.max_block_transactions = max_transactions;
+        self.max_block_size = max_size;
+        Ok(())
+    }
+
+    /// Checks a block against the configured transaction count and size limits
+    fn check_block_limits(&self, block: &Block) -> IcnResult<()> {
+        if block.transactions.len() > self.max_block_transactions {
+            return Err(IcnError::Consensus(format!(
+                "Block has {} transactions, exceeding the limit of {}",
+                block.transactions.len(), self.max_block_transactions
+            )));
+        }
+        let size = serde_json::to_vec(block)
+            .map_err(|e| IcnError::Serialization(format!("Failed to serialize block: {}", e)))?
+            .len();
+        if size > self.max_block_size {
+            return Err(IcnError::Consensus(format!(
+                "Block is {} bytes, exceeding the limit of {} bytes",
+                size, self.max_block_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the current validator epoch
+    pub fn current_epoch(&self) -> IcnResult<u64> {
+        Ok(self.validator_set.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for validator_set".to_string()))?.epoch)
+    }
+
+    /// Returns the validator set of the current epoch
+    ///
+    /// The set is selected once per epoch, so stake and reputation changes only affect
+    /// validation from the next epoch boundary. If no set could be selected at the
+    /// boundary, selection is retried here.
+    pub fn current_validators(&self) -> IcnResult<Vec<String>> {
+        Ok(self.current_validator_scores()?.into_iter().map(|(peer_id, _)| peer_id).collect())
+    }
+
+    /// Returns the validator set of the current epoch with the scores taken at selection
+    fn current_validator_scores(&self) -> IcnResult<Vec<(String, f64)>> {
+        let mut validator_set = self.validator_set.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for validator_set".to_string()))?;
+        if let Some(validators) = &validator_set.validators {
+            return Ok(validators.clone());
+        }
+        let validators = self.select_validators()?;
+        validator_set.validators = Some(validators.clone());
+        Ok(validators)
+    }
+
+    /// Moves to the epoch containing the given block height, selecting a new validator set
+    /// when an epoch boundary is crossed
+    fn advance_epoch(&self, block_index: u64) -> IcnResult<()> {
+        let epoch = block_index / self.epoch_length;
+        let mut validator_set = self.validator_set.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for validator_set".to_string()))?;
+        if validator_set.epoch == epoch && validator_set.validators.is_some() {
+            return Ok(());
+        }
+
+        validator_set.epoch = epoch;
+        validator_set.validators = match self.select_validators() {
+            Ok(validators) => {
+                info!("Selected {} validators for epoch {}", validators.len(), epoch);
+                Some(validators)
+            }
+            Err(e) => {
+                warn!("Failed to select validators for epoch {}: {}", epoch, e);
+                None
+            }
+        };
+        Ok(())
+    }
+
     /// Calculates the score of a validator based on stake and reputation
     fn calculate_validator_score(&self, peer_id: &str) -> f64 {
         let stake = self.stake_info.read().unwrap()
@@ -207,16 +499,72 @@ impl ProofOfCooperation {
 
     /// Converts a hash string to a float value between 0 and 1
     fn hash_to_float(&self, hash: &str) -> f64 {
-        let numeric_hash = u64::from_str_radix(&hash[0..16], 16).unwrap_or(0);
+        let numeric_hash = hash.get(0..16).and_then(|prefix| u64::from_str_radix(prefix, 16).ok()).unwrap_or(0);
         numeric_hash as f64 / u64::MAX as f64
     }
 
+    /// Returns the peers that may propose blocks with their selection weights, ordered by peer ID
+    ///
+    /// Proposers are the validators of the current epoch, weighted by the scores they had
+    /// when the epoch's validator set was selected.
+    fn eligible_proposers(&self) -> IcnResult<Vec<(String, f64)>> {
+        let mut proposers: Vec<(String, f64)> = self.current_validator_scores()?
+            .into_iter()
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        proposers.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(proposers)
+    }
+
+    /// Selects the proposer of the block following the block with the given hash
+    ///
+    /// The selection is weighted by the epoch's stake and reputation snapshot and seeded from
+    /// the previous hash, so every node with the same state selects the same proposer.
+    fn proposer_for(&self, previous_hash: &str) -> IcnResult<String> {
+        let proposers = self.eligible_proposers()?;
+        let total_score: f64 = proposers.iter().map(|(_, score)| score).sum();
+        if proposers.is_empty() || total_score <= 0.0 {
+            return Err(IcnError::Consensus("No eligible proposers available".to_string()));
+        }
+
+        let target = self.hash_to_float(previous_hash) * total_score;
+        let mut cumulative_weight = 0.0;
+        for (peer_id, score) in &proposers {
+            cumulative_weight += score;
+            if cumulative_weight >= target {
+                return Ok(peer_id.clone());
+            }
+        }
+
+        // Rounding can leave the target just above the final cumulative weight
+        Ok(proposers[proposers.len() - 1].0.clone())
+    }
+
+    /// Returns the peer expected to propose the block following `prev_block`
+    pub fn expected_proposer(&self, prev_block: &Block) -> IcnResult<String> {
+        self.proposer_for(&prev_block.hash)
+    }
+
     /// Validates a block by selecting validators and conducting a stake-weighted vote
     fn validate(&self, block: &Block) -> IcnResult<bool> {
         let known_peers = self.known_peers.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for known_peers".to_string()))?;
         if !known_peers.contains(&block.proposer_id) {
             return Err(IcnError::Consensus(format!("Unknown proposer: {}", block.proposer_id)));
         }
+        drop(known_peers);
+
+        let expected_proposer = self.proposer_for(&block.previous_hash)?;
+        if block.proposer_id != expected_proposer {
+            return Err(IcnError::Consensus(format!(
+                "Block proposed by {} but the expected proposer is {}",
+                block.proposer_id, expected_proposer
+            )));
+        }
+
+        if let Err(e) = self.check_block_limits(block) {
+            self.update_reputation(&block.proposer_id, false)?;
+            return Err(e);
+        }
 
         let current_time = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| IcnError::Consensus(format!("System time error: {}", e)))?.as_secs();
         let last_block_time = *self.last_block_time.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for last_block_time".to_string()))?;
@@ -224,11 +572,12 @@ impl ProofOfCooperation {
             return Err(IcnError::Consensus("Block proposed too soon".to_string()));
         }
 
-        let validators = self.select_validators()?;
+        let validators = self.current_validators()?;
         let mut valid_votes = 0;
         let total_votes = validators.len();
 
         for validator in validators {
+            self.record_activity(&validator, current_time)?;
             let vote = self.stake_weighted_vote(&validator, block)?;
             if vote {
                 valid_votes += 1;
@@ -238,6 +587,8 @@ impl ProofOfCooperation {
         let validation_threshold = (total_votes as f64 * 2.0 / 3.0).ceil() as usize;
         let is_valid = valid_votes >= validation_threshold;
 
+        self.record_activity(&block.proposer_id, current_time)?;
+
         self.update_reputation(&block.proposer_id, is_valid)?;
         Ok(is_valid)
     }
@@ -275,7 +626,7 @@ impl ProofOfCooperation {
             WEIGHT_CONSISTENCY * consistency +
             WEIGHT_QUALITY * coop_score * quality_factor +
             WEIGHT_IMPACT * network_impact
-        ) * DEFAULT_REPUTATION_DECAY + (1.0 - DEFAULT_REPUTATION_DECAY) * *rep_score;
+        ) * REPUTATION_SMOOTHING + (1.0 - REPUTATION_SMOOTHING) * *rep_score;
 
         *rep_score = new_rep_score.max(0.0).min(1.0);
         Ok(())
@@ -321,24 +672,8 @@ impl Consensus for ProofOfCooperation {
     }
 
     fn select_proposer(&self) -> IcnResult<String> {
-        let eligible_peers = self.get_eligible_peers();
-        if eligible_peers.is_empty() {
-            return Err(IcnError::Consensus("No eligible proposers available".to_string()));
-        }
-
-        let total_score: f64 = eligible_peers.iter().map(|peer_id| self.calculate_validator_score(peer_id)).sum();
-        let mut rng = rand::thread_rng();
-        let mut cumulative_weight = 0.0;
-        let random_value = rng.gen::<f64>() * total_score;
-
-        for peer_id in &eligible_peers {
-            cumulative_weight += self.calculate_validator_score(peer_id);
-            if cumulative_weight >= random_value {
-                return Ok(peer_id.clone());
-            }
-        }
-
-        Err(IcnError::Consensus("Failed to select proposer".to_string()))
+        let last_block_hash = self.last_block_hash.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for last_block_hash".to_string()))?.clone();
+        self.proposer_for(&last_block_hash)
     }
 
     fn get_eligible_peers(&self) -> Vec<String> {
@@ -346,14 +681,38 @@ impl Consensus for ProofOfCooperation {
     }
 
     fn update_state(&self, latest_block: &Block) -> IcnResult<()> {
-        let mut last_block_time = self.last_block_time.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_time".to_string()))?;
-        *last_block_time = latest_block.timestamp;
-        Ok(())
+        {
+            let mut last_block_time = self.last_block_time.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_time".to_string()))?;
+            *last_block_time = latest_block.timestamp;
+            *self.last_block_hash.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_hash".to_string()))? = latest_block.hash.clone();
+        }
+        self.advance_epoch(latest_block.index)
     }
 
     fn initialize(&self, latest_block: &Block) -> IcnResult<()> {
-        let mut last_block_time = self.last_block_time.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_time".to_string()))?;
-        *last_block_time = latest_block.timestamp;
+        {
+            let mut last_block_time = self.last_block_time.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_time".to_string()))?;
+            *last_block_time = latest_block.timestamp;
+            *self.last_block_hash.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for last_block_hash".to_string()))? = latest_block.hash.clone();
+        }
+        self.advance_epoch(latest_block.index)
+    }
+
+    fn register_validator(&self, validator_id: &str, stake: u64, reputation: f64) -> IcnResult<()> {
+        if !(0.0..=1.0).contains(&reputation) {
+            return Err(IcnError::Consensus(format!("Validator reputation must be in [0, 1], got {}", reputation)));
+        }
+        let is_known = self.known_peers.read().map_err(|_| IcnError::Consensus("Failed to acquire read lock for known_peers".to_string()))?.contains(validator_id);
+        if !is_known {
+            self.register_peer(validator_id)?;
+        }
+
+        if let Some(info) = self.stake_info.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for stake_info".to_string()))?.get_mut(validator_id) {
+            info.amount = stake;
+        }
+        self.reputation_scores.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for reputation_scores".to_string()))?
+            .insert(validator_id.to_string(), reputation);
+        info!("Registered validator {} with stake {}", validator_id, stake);
         Ok(())
     }
 
@@ -391,6 +750,7 @@ impl ProofOfCooperation {
         self.computational_power.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for computational_power".to_string()))?.remove(peer_id);
         self.storage_provision.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for storage_provision".to_string()))?.remove(peer_id);
         self.governance_participation.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for governance_participation".to_string()))?.remove(peer_id);
+        self.activity.write().map_err(|_| IcnError::Consensus("Failed to acquire write lock for activity".to_string()))?.remove(peer_id);
         Ok(())
     }
 
@@ -528,4 +888,222 @@ mod tests {
         let new_health_score = poc.evaluate_network_health().unwrap();
         assert!(new_health_score < health_score);
     }
+
+    #[test]
+    fn test_reputation_decays_for_inactive_peers() {
+        let poc = setup_test_poc();
+        for peer in ["peer1", "peer2"] {
+            poc.stake_info.write().unwrap().get_mut(peer).unwrap().amount = 2000;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3 * DEFAULT_REPUTATION_DECAY_EPOCH;
+        poc.record_activity("peer1", now).unwrap();
+
+        let initial_ratio = poc.calculate_validator_score("peer2") / poc.calculate_validator_score("peer1");
+        poc.apply_reputation_decay(now).unwrap();
+        let decayed_ratio = poc.calculate_validator_score("peer2") / poc.calculate_validator_score("peer1");
+
+        assert_eq!(poc.reputation_scores.read().unwrap()["peer1"], 1.0);
+        assert!((poc.reputation_scores.read().unwrap()["peer2"] - DEFAULT_REPUTATION_DECAY.powi(3)).abs() < 1e-9);
+        assert!(decayed_ratio < initial_ratio);
+
+        // Epochs that were already decayed are not decayed again
+        poc.apply_reputation_decay(now).unwrap();
+        assert!((poc.reputation_scores.read().unwrap()["peer2"] - DEFAULT_REPUTATION_DECAY.powi(3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reputation_decay_stops_at_floor() {
+        let mut poc = setup_test_poc();
+        assert!(poc.set_reputation_decay(1.5, 0.2).is_err());
+        poc.set_reputation_decay(0.5, 0.2).unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 100 * DEFAULT_REPUTATION_DECAY_EPOCH;
+        poc.apply_reputation_decay(now).unwrap();
+        assert_eq!(poc.reputation_scores.read().unwrap()["peer1"], 0.2);
+    }
+
+    #[test]
+    fn test_reputation_decay_does_not_change_smoothing() {
+        let default_poc = setup_test_poc();
+        let mut poc = setup_test_poc();
+        poc.set_reputation_decay(1.0, 0.1).unwrap();
+
+        default_poc.update_reputation("peer1", true).unwrap();
+        poc.update_reputation("peer1", true).unwrap();
+        assert_eq!(
+            poc.reputation_scores.read().unwrap()["peer1"],
+            default_poc.reputation_scores.read().unwrap()["peer1"]
+        );
+    }
+
+    #[test]
+    fn test_reputation_decay_epoch_is_configurable() {
+        let mut poc = setup_test_poc();
+        assert!(poc.set_reputation_decay_epoch(0).is_err());
+        poc.set_reputation_decay_epoch(60).unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 2 * 60;
+        poc.apply_reputation_decay(now).unwrap();
+        assert!((poc.reputation_scores.read().unwrap()["peer1"] - DEFAULT_REPUTATION_DECAY.powi(2)).abs() < 1e-9);
+    }
+
+    fn setup_staked_poc() -> ProofOfCooperation {
+        let poc = setup_test_poc();
+        for (peer, stake) in [("peer1", 2000), ("peer2", 3000), ("peer3", 4000)] {
+            poc.stake_info.write().unwrap().get_mut(peer).unwrap().amount = stake;
+        }
+        poc
+    }
+
+    #[test]
+    fn test_proposer_selection_is_deterministic() {
+        let poc_a = setup_staked_poc();
+        let poc_b = setup_staked_poc();
+
+        let mut selected = HashSet::new();
+        for i in 0..20 {
+            let prev_block = Block::new(i, vec![format!("tx-{}", i)], "previous_hash".to_string(), "peer1".to_string());
+            let proposer = poc_a.expected_proposer(&prev_block).unwrap();
+            assert_eq!(proposer, poc_b.expected_proposer(&prev_block).unwrap());
+
+            poc_a.update_state(&prev_block).unwrap();
+            assert_eq!(poc_a.select_proposer().unwrap(), proposer);
+            selected.insert(proposer);
+        }
+        assert!(selected.len() > 1);
+    }
+
+    #[test]
+    fn test_block_from_unexpected_proposer_is_rejected() {
+        let poc = setup_staked_poc();
+        let prev_block = Block::new(0, vec![], "genesis".to_string(), "peer1".to_string());
+        let expected = poc.expected_proposer(&prev_block).unwrap();
+        let other = ["peer1", "peer2", "peer3"].into_iter().find(|peer| *peer != expected).unwrap();
+
+        let block = Block::new(1, vec![], prev_block.hash.clone(), other.to_string());
+        match poc.validate(&block) {
+            Err(IcnError::Consensus(msg)) => assert!(msg.contains("expected proposer")),
+            other => panic!("Expected proposer mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_proposers_come_from_epoch_validator_set() {
+        let poc = setup_staked_poc();
+        let prev_blocks: Vec<Block> = (0..20)
+            .map(|i| Block::new(i, vec![format!("tx-{}", i)], "previous_hash".to_string(), "peer1".to_string()))
+            .collect();
+        let expected: Vec<String> = prev_blocks.iter().map(|block| poc.expected_proposer(block).unwrap()).collect();
+
+        // Stake and reputation changes within the epoch do not move the selection
+        poc.register_peer("peer4").unwrap();
+        poc.stake_info.write().unwrap().get_mut("peer4").unwrap().amount = 1_000_000;
+        poc.stake_info.write().unwrap().get_mut("peer1").unwrap().amount = 0;
+        poc.reputation_scores.write().unwrap().insert("peer2".to_string(), 0.1);
+        for (block, proposer) in prev_blocks.iter().zip(&expected) {
+            assert_eq!(&poc.expected_proposer(block).unwrap(), proposer);
+        }
+    }
+
+    #[test]
+    fn test_validator_set_changes_only_at_epoch_boundary() {
+        let mut poc = setup_staked_poc();
+        poc.set_epoch_length(2).unwrap();
+        poc.register_peer("peer4").unwrap();
+        poc.stake_info.write().unwrap().get_mut("peer4").unwrap().amount = 500;
+
+        let block = |index| Block::new(index, vec![], "previous_hash".to_string(), "peer1".to_string());
+        poc.update_state(&block(0)).unwrap();
+        assert_eq!(poc.current_epoch().unwrap(), 0);
+        let mut validators = poc.current_validators().unwrap();
+        validators.sort();
+        assert_eq!(validators, vec!["peer1", "peer2", "peer3"]);
+
+        // peer4 rises above MIN_STAKE_SYBIL mid-epoch but is not added until the boundary
+        poc.stake_info.write().unwrap().get_mut("peer4").unwrap().amount = MIN_STAKE_SYBIL + 1;
+        poc.update_state(&block(1)).unwrap();
+        assert_eq!(poc.current_epoch().unwrap(), 0);
+        assert!(!poc.current_validators().unwrap().contains(&"peer4".to_string()));
+
+        poc.update_state(&block(2)).unwrap();
+        assert_eq!(poc.current_epoch().unwrap(), 1);
+        assert!(poc.current_validators().unwrap().contains(&"peer4".to_string()));
+        assert!(poc.set_epoch_length(0).is_err());
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let poc = setup_staked_poc();
+        poc.update_reputation("peer1", false).unwrap();
+
+        let mut saved = Vec::new();
+        poc.save_state(&mut saved).unwrap();
+        let restored = ProofOfCooperation::new();
+        restored.load_state(saved.as_slice()).unwrap();
+
+        assert_eq!(*restored.known_peers.read().unwrap(), *poc.known_peers.read().unwrap());
+        assert_eq!(*restored.reputation_scores.read().unwrap(), *poc.reputation_scores.read().unwrap());
+        for peer in ["peer1", "peer2", "peer3"] {
+            assert_eq!(restored.stake_info.read().unwrap()[peer].amount, poc.stake_info.read().unwrap()[peer].amount);
+        }
+    }
+
+    #[test]
+    fn test_stale_state_is_loaded_gracefully() {
+        let poc = setup_staked_poc();
+        let mut saved = Vec::new();
+        poc.save_state(&mut saved).unwrap();
+
+        // A ghost peer with data but no registration, and peer3 with registration but no reputation
+        let mut state: serde_json::Value = serde_json::from_slice(&saved).unwrap();
+        state["stake_info"]["ghost"] = state["stake_info"]["peer1"].clone();
+        state["reputation_scores"].as_object_mut().unwrap().remove("peer3");
+        let restored = ProofOfCooperation::new();
+        restored.load_state(serde_json::to_vec(&state).unwrap().as_slice()).unwrap();
+
+        assert!(!restored.stake_info.read().unwrap().contains_key("ghost"));
+        assert_eq!(restored.reputation_scores.read().unwrap()["peer3"], 1.0);
+        assert_eq!(restored.stake_info.read().unwrap()["peer3"].amount, 4000);
+
+        state["version"] = serde_json::json!(STATE_VERSION + 1);
+        assert!(restored.load_state(serde_json::to_vec(&state).unwrap().as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_oversized_blocks_are_rejected() {
+        let prev_block = Block::new(0, vec![], "genesis".to_string(), "peer1".to_string());
+        let oversized = [
+            (vec!["tx".to_string(); 4], "4 transactions"),
+            (vec!["x".repeat(1_000)], "bytes"),
+        ];
+
+        for (transactions, expected_error) in oversized {
+            let mut poc = setup_staked_poc();
+            poc.set_block_limits(3, 1_000).unwrap();
+            let proposer = poc.expected_proposer(&prev_block).unwrap();
+            let reputation = poc.reputation_scores.read().unwrap()[&proposer];
+
+            let block = Block::new(1, transactions, prev_block.hash.clone(), proposer.clone());
+            match poc.validate(&block) {
+                Err(IcnError::Consensus(msg)) => assert!(msg.contains(expected_error), "{}", msg),
+                other => panic!("Expected block limit error, got {:?}", other),
+            }
+            assert!(poc.reputation_scores.read().unwrap()[&proposer] < reputation);
+        }
+
+        assert!(setup_staked_poc().set_block_limits(0, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_get_reputation() {
+        let poc = setup_staked_poc();
+        assert_eq!(poc.get_reputation("peer1").unwrap(), 1.0);
+        poc.update_reputation("peer1", false).unwrap();
+        assert!(poc.get_reputation("peer1").unwrap() < 1.0);
+
+        match poc.get_reputation("unknown_peer") {
+            Err(IcnError::Consensus(msg)) => assert!(msg.contains("Unknown peer")),
+            other => panic!("Expected unknown peer error, got {:?}", other),
+        }
+    }
 }
@@ -1,20 +1,149 @@
 // File: icn_core/src/config/config_loader.rs
 
 use std::fs;
+use std::path::Path;
 use serde::Deserialize;
 use icn_shared::{IcnError, IcnResult};
 use log::{info, debug, error};
 
-/// Represents the application configuration loaded from a TOML file.
+/// Ports below this value are reserved for system services and may not be used by the server.
+const MIN_UNRESERVED_PORT: u16 = 1024;
+
+/// Log levels accepted in the `log_level` setting.
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Represents the application configuration loaded from a TOML or JSON file.
 ///
 /// This struct holds configuration details necessary for the server and database
-/// components of the application. It is deserialized from a TOML file.
+/// components of the application. The optional settings fall back to their defaults
+/// when missing, so older configuration files keep loading.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     /// Configuration for the server, such as host, port, and TLS settings.
     pub server: ServerConfig,
     /// Configuration for the database, including connection URLs.
     pub database: DatabaseConfig,
+    /// Directory where the node keeps its data.
+    #[serde(default = "default_data_dir")]
+    pub data_dir: String,
+    /// The log level (error, warn, info, debug, trace).
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Addresses of peers contacted at startup.
+    #[serde(default)]
+    pub bootstrap_peers: Vec<String>,
+    /// Interval between produced blocks, in seconds.
+    #[serde(default = "default_block_interval_secs")]
+    pub block_interval_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            data_dir: default_data_dir(),
+            log_level: default_log_level(),
+            bootstrap_peers: Vec::new(),
+            block_interval_secs: default_block_interval_secs(),
+        }
+    }
+}
+
+fn default_data_dir() -> String {
+    "data".to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_block_interval_secs() -> u64 {
+    10
+}
+
+impl Config {
+    /// Loads the configuration from a file.
+    ///
+    /// Files with a `.json` extension are parsed as JSON; all others are parsed as TOML.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_path` - The path to the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Config>` - The parsed configuration if successful, otherwise an `IcnError`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an `IcnError::Config` if the file cannot be read or parsed.
+    pub fn load(config_path: &str) -> IcnResult<Self> {
+        // Read the contents of the configuration file
+        let config_content = fs::read_to_string(config_path)
+            .map_err(|e| {
+                error!("Failed to read config file '{}': {}", config_path, e);
+                IcnError::Config(format!("Failed to read config file '{}': {}", config_path, e))
+            })?;
+
+        debug!("Configuration file content loaded successfully");
+
+        let is_json = Path::new(config_path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+        if is_json {
+            serde_json::from_str(&config_content)
+                .map_err(|e| {
+                    error!("Failed to parse JSON from '{}': {}", config_path, e);
+                    IcnError::Config(format!("Failed to parse JSON from '{}': {}", config_path, e))
+                })
+        } else {
+            toml::from_str(&config_content)
+                .map_err(|e| {
+                    error!("Failed to parse TOML from '{}': {}", config_path, e);
+                    IcnError::Config(format!("Failed to parse TOML from '{}': {}", config_path, e))
+                })
+        }
+    }
+
+    /// Checks that the configuration values are usable.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - `Ok(())` if the configuration is valid, otherwise an `IcnError`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an `IcnError::Config` describing the first invalid setting found.
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.server.host.trim().is_empty() {
+            return Err(IcnError::Config("server.host must not be empty".to_string()));
+        }
+        if self.server.port < MIN_UNRESERVED_PORT {
+            return Err(IcnError::Config(format!(
+                "server.port {} is reserved; use a port of at least {}",
+                self.server.port, MIN_UNRESERVED_PORT
+            )));
+        }
+        if self.data_dir.trim().is_empty() {
+            return Err(IcnError::Config("data_dir must not be empty".to_string()));
+        }
+        if !LOG_LEVELS.contains(&self.log_level.to_lowercase().as_str()) {
+            return Err(IcnError::Config(format!(
+                "log_level '{}' is not one of {}",
+                self.log_level,
+                LOG_LEVELS.join(", ")
+            )));
+        }
+        if self.block_interval_secs == 0 {
+            return Err(IcnError::Config("block_interval_secs must be greater than zero".to_string()));
+        }
+        if let Some(peer) = self.bootstrap_peers.iter().find(|peer| peer.trim().is_empty()) {
+            return Err(IcnError::Config(format!("bootstrap_peers contains an empty address: '{}'", peer)));
+        }
+        Ok(())
+    }
 }
 
 /// Configuration for the server, including network and TLS settings.
@@ -52,10 +181,23 @@ pub struct ServerConfig {
     pub cert_password: String,
 }
 
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            debug: false,
+            cert_file_path: "cert.pem".to_string(),
+            key_file_path: "key.pem".to_string(),
+            cert_password: String::new(),
+        }
+    }
+}
+
 /// Configuration for the database, including connection URLs.
 ///
 /// This struct holds the configuration required to connect to one or more databases.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct DatabaseConfig {
     /// A list of database connection URLs.
     pub urls: Vec<String>,
@@ -72,11 +214,12 @@ pub struct ConfigLoader {
 }
 
 impl ConfigLoader {
-    /// Creates a new `ConfigLoader` instance by loading and parsing a TOML configuration file.
+    /// Creates a new `ConfigLoader` instance by loading, parsing and validating a
+    /// configuration file.
     ///
     /// # Arguments
     ///
-    /// * `config_path` - The path to the TOML configuration file.
+    /// * `config_path` - The path to the TOML or JSON configuration file.
     ///
     /// # Returns
     ///
@@ -84,25 +227,13 @@ impl ConfigLoader {
     ///
     /// # Errors
     ///
-    /// * Returns an `IcnError::Config` if the file cannot be read or parsed.
+    /// * Returns an `IcnError::Config` if the file cannot be read or parsed, or if the
+    ///   configuration is invalid.
     pub fn new(config_path: &str) -> IcnResult<Self> {
         info!("Loading configuration from file: {}", config_path);
-        
-        // Read the contents of the configuration file
-        let config_content = fs::read_to_string(config_path)
-            .map_err(|e| {
-                error!("Failed to read config file '{}': {}", config_path, e);
-                IcnError::Config(format!("Failed to read config file '{}': {}", config_path, e))
-            })?;
-
-        debug!("Configuration file content loaded successfully");
 
-        // Parse the TOML content into a Config struct
-        let config: Config = toml::from_str(&config_content)
-            .map_err(|e| {
-                error!("Failed to parse TOML from '{}': {}", config_path, e);
-                IcnError::Config(format!("Failed to parse TOML from '{}': {}", config_path, e))
-            })?;
+        let config = Config::load(config_path)?;
+        config.validate()?;
 
         info!("Configuration loaded and parsed successfully");
         debug!("Loaded configuration: {:?}", config);
@@ -174,4 +305,95 @@ mod tests {
             ]
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    /// Tests loading a JSON configuration, selected by the file extension.
+    fn test_load_json_config() {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(file, r#"{{
+            "server": {{
+                "host": "0.0.0.0",
+                "port": 9000,
+                "debug": false,
+                "cert_file_path": "cert.pem",
+                "key_file_path": "key.pem",
+                "cert_password": ""
+            }},
+            "database": {{ "urls": [] }},
+            "log_level": "debug",
+            "bootstrap_peers": ["10.0.0.1:9000"],
+            "block_interval_secs": 5
+        }}"#).unwrap();
+
+        let config = Config::load(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(config.log_level, "debug");
+        assert_eq!(config.bootstrap_peers, vec!["10.0.0.1:9000".to_string()]);
+        assert_eq!(config.block_interval_secs, 5);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    /// Tests that settings missing from older configuration files take their defaults.
+    fn test_missing_optional_settings_use_defaults() {
+        let test_config = create_test_config();
+        let config = Config::load(test_config.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(config.data_dir, "data");
+        assert_eq!(config.log_level, "info");
+        assert!(config.bootstrap_peers.is_empty());
+        assert_eq!(config.block_interval_secs, 10);
+    }
+
+    #[test]
+    /// Tests that the default configuration is valid.
+    fn test_default_config_is_valid() {
+        let config = Config::default();
+        assert_eq!(config.server.port, 8080);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    /// Tests that each invalid setting is rejected.
+    fn test_validate_rejects_invalid_settings() {
+        let invalid: Vec<fn(&mut Config)> = vec![
+            |c| c.server.host = " ".to_string(),
+            |c| c.server.port = 0,
+            |c| c.server.port = 80,
+            |c| c.data_dir = String::new(),
+            |c| c.log_level = "verbose".to_string(),
+            |c| c.block_interval_secs = 0,
+            |c| c.bootstrap_peers = vec![String::new()],
+        ];
+
+        for make_invalid in invalid {
+            let mut config = Config::default();
+            make_invalid(&mut config);
+            assert!(matches!(config.validate(), Err(IcnError::Config(_))), "accepted {:?}", config);
+        }
+    }
+
+    #[test]
+    /// Tests that `ConfigLoader` refuses an invalid configuration and unparsable files.
+    fn test_config_loader_rejects_invalid_config() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, r#"
+            [server]
+            host = "localhost"
+            port = 22
+            debug = false
+            cert_file_path = "cert.pem"
+            key_file_path = "key.pem"
+            cert_password = ""
+
+            [database]
+            urls = []
+        "#).unwrap();
+        assert!(matches!(ConfigLoader::new(file.path().to_str().unwrap()), Err(IcnError::Config(_))));
+
+        let mut garbage = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(garbage, "not json").unwrap();
+        assert!(matches!(Config::load(garbage.path().to_str().unwrap()), Err(IcnError::Config(_))));
+    }
+}
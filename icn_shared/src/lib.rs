@@ -55,6 +55,73 @@ pub struct Block {
     pub hash: String,
     pub proposer_id: String,
     pub nonce: u64,
+    /// Merkle root of the block's transactions. Empty for blocks created before
+    /// Merkle roots were introduced, whose hash covers the transactions directly.
+    #[serde(default)]
+    pub merkle_root: String,
+}
+
+/// A sibling hash on the path from a transaction to the Merkle root of its block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MerkleProofStep {
+    pub sibling: String,
+    pub sibling_is_left: bool,
+}
+
+/// Hashes a transaction as a leaf of a block's Merkle tree.
+pub fn hash_transaction(transaction: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(transaction.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hashes two child nodes of a Merkle tree.
+fn hash_merkle_nodes(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Builds every level of the Merkle tree of the given transactions, from the leaves up
+/// to the root. The last node of a level with an odd number of nodes is promoted to the
+/// next level unchanged; pairing it with itself would give `[a, b, c]` and `[a, b, c, c]`
+/// the same root.
+fn merkle_levels(transactions: &[String]) -> Vec<Vec<String>> {
+    let mut levels = vec![transactions.iter().map(|tx| hash_transaction(tx)).collect::<Vec<_>>()];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let next = levels.last().unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_merkle_nodes(left, right),
+                _ => pair[0].clone(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Calculates the Merkle root of the given transactions.
+pub fn merkle_root(transactions: &[String]) -> String {
+    match merkle_levels(transactions).last().and_then(|level| level.first()) {
+        Some(root) => root.clone(),
+        None => format!("{:x}", Sha256::digest(b"")),
+    }
+}
+
+/// Verifies that the transaction with hash `tx_hash` is included under the Merkle `root`.
+pub fn verify_merkle_proof(root: &str, tx_hash: &str, proof: &[MerkleProofStep]) -> bool {
+    let computed = proof.iter().fold(tx_hash.to_string(), |hash, step| {
+        if step.sibling_is_left {
+            hash_merkle_nodes(&step.sibling, &hash)
+        } else {
+            hash_merkle_nodes(&hash, &step.sibling)
+        }
+    });
+    computed == root
 }
 
 impl Block {
@@ -65,6 +132,7 @@ impl Block {
             .expect("Time went backwards")
             .as_secs();
 
+        let merkle_root = merkle_root(&transactions);
         let mut block = Block {
             index,
             timestamp,
@@ -73,6 +141,7 @@ impl Block {
             hash: String::new(),
             proposer_id,
             nonce: 0,
+            merkle_root,
         };
         block.hash = block.calculate_hash();
         block
@@ -83,16 +152,44 @@ impl Block {
         let mut hasher = Sha256::new();
         hasher.update(self.index.to_be_bytes());
         hasher.update(self.timestamp.to_be_bytes());
-        hasher.update(serde_json::to_string(&self.transactions).unwrap());
+        if self.merkle_root.is_empty() {
+            hasher.update(serde_json::to_string(&self.transactions).unwrap());
+        } else {
+            hasher.update(&self.merkle_root);
+        }
         hasher.update(&self.previous_hash);
         hasher.update(&self.proposer_id);
         hasher.update(self.nonce.to_be_bytes());
         format!("{:x}", hasher.finalize())
     }
 
-    /// Verifies the block's integrity by checking its hash.
+    /// Verifies the block's integrity by checking its hash and Merkle root.
     pub fn is_valid(&self) -> bool {
         self.hash == self.calculate_hash()
+            && (self.merkle_root.is_empty() || self.merkle_root == merkle_root(&self.transactions))
+    }
+
+    /// Builds a proof that the transaction at `tx_index` is included in the block.
+    pub fn merkle_proof(&self, tx_index: usize) -> Option<Vec<MerkleProofStep>> {
+        if tx_index >= self.transactions.len() {
+            return None;
+        }
+
+        let levels = merkle_levels(&self.transactions);
+        let mut index = tx_index;
+        let mut proof = Vec::new();
+        for level in &levels[..levels.len() - 1] {
+            // The last node of an odd-sized level has no sibling and is promoted as is
+            let sibling_index = index ^ 1;
+            if sibling_index < level.len() {
+                proof.push(MerkleProofStep {
+                    sibling: level[sibling_index].clone(),
+                    sibling_is_left: sibling_index < index,
+                });
+            }
+            index /= 2;
+        }
+        Some(proof)
     }
 }
 
@@ -146,7 +243,8 @@ mod tests {
         block.transactions.push("tx3".into());
         assert!(!block.is_valid());
 
-        // Recalculate the hash
+        // Recalculate the Merkle root and hash
+        block.merkle_root = merkle_root(&block.transactions);
         block.hash = block.calculate_hash();
         assert!(block.is_valid());
     }
@@ -171,4 +269,51 @@ mod tests {
         let error = IcnError::Network("Connection failed".to_string());
         assert_eq!(error.to_string(), "Network error: Connection failed");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merkle_proofs() {
+        let transactions: Vec<String> = (0..5).map(|i| format!("tx{}", i)).collect();
+        let block = Block::new(3, transactions.clone(), "prev_hash".to_string(), "proposer4".to_string());
+
+        for (tx_index, transaction) in transactions.iter().enumerate() {
+            let proof = block.merkle_proof(tx_index).unwrap();
+            assert!(verify_merkle_proof(&block.merkle_root, &hash_transaction(transaction), &proof));
+        }
+        assert!(block.merkle_proof(5).is_none());
+    }
+
+    #[test]
+    fn test_duplicated_trailing_transaction_changes_merkle_root() {
+        let transactions: Vec<String> = ["a", "b", "c"].iter().map(|tx| tx.to_string()).collect();
+        let mut duplicated = transactions.clone();
+        duplicated.push("c".to_string());
+        assert_ne!(merkle_root(&transactions), merkle_root(&duplicated));
+
+        let mut block = Block::new(6, transactions, "prev_hash".to_string(), "proposer7".to_string());
+        block.transactions.push("c".to_string());
+        assert!(!block.is_valid());
+    }
+
+    #[test]
+    fn test_tampered_merkle_proof() {
+        let transactions: Vec<String> = (0..4).map(|i| format!("tx{}", i)).collect();
+        let block = Block::new(4, transactions.clone(), "prev_hash".to_string(), "proposer5".to_string());
+
+        let mut proof = block.merkle_proof(1).unwrap();
+        assert!(!verify_merkle_proof(&block.merkle_root, &hash_transaction("tx9"), &proof));
+        proof[0].sibling = hash_transaction("tx9");
+        assert!(!verify_merkle_proof(&block.merkle_root, &hash_transaction(&transactions[1]), &proof));
+    }
+
+    #[test]
+    fn test_block_without_merkle_root() {
+        let block = Block::new(5, vec!["tx6".into()], "prev_hash".to_string(), "proposer6".to_string());
+        let mut json: serde_json::Value = serde_json::to_value(&block).unwrap();
+        json.as_object_mut().unwrap().remove("merkle_root");
+
+        let mut legacy: Block = serde_json::from_value(json).unwrap();
+        assert!(legacy.merkle_root.is_empty());
+        legacy.hash = legacy.calculate_hash();
+        assert!(legacy.is_valid());
+    }
+}
@@ -0,0 +1,559 @@
+// File: icn_storage/src/file_backend.rs
+
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use icn_shared::{Block, IcnError, IcnResult};
+use sha2::{Sha256, Digest};
+use crate::state_storage::{StateChange, StateStorage};
+
+/// Name of the append-only file holding all blocks.
+const BLOCKS_FILE: &str = "blocks.dat";
+/// Name of the file holding the offset of every block record in `BLOCKS_FILE`, by height.
+const BLOCK_INDEX_FILE: &str = "blocks.idx";
+/// Name of the file holding the latest state snapshot.
+const STATE_FILE: &str = "state.json";
+/// Name of the temporary file a state snapshot is written to before it replaces `STATE_FILE`.
+const STATE_TEMP_FILE: &str = "state.json.tmp";
+/// Name of the append-only file holding the state changes made since the latest snapshot.
+const STATE_LOG_FILE: &str = "state.log";
+/// Number of logged state changes after which the state should be compacted into a snapshot.
+const STATE_LOG_COMPACTION_THRESHOLD: usize = 1_000;
+/// Size of a record header: a 4-byte payload length, the SHA-256 checksum of the payload,
+/// and a 4-byte checksum of the two.
+const RECORD_HEADER_SIZE: usize = 4 + 32 + 4;
+/// Length of the part of a record header covered by its header checksum.
+const RECORD_HEADER_BODY_SIZE: usize = 4 + 32;
+/// Size of a block index entry: the 8-byte offset of the block's record.
+const INDEX_ENTRY_SIZE: u64 = 8;
+
+/// A record read from a file: its offset and its payload.
+type Record<'a> = (usize, &'a [u8]);
+/// A record file opened for appending, the offset and payload of each of its complete
+/// records, and the length of those records.
+type OpenedRecordFile = (File, Vec<(u64, Vec<u8>)>, u64);
+
+/// `FileBackend` persists blocks and state under a data directory.
+///
+/// Blocks are appended to a single file as length-prefixed, checksummed records, and the
+/// offset of each record is appended to an index file so a block can be read by height.
+/// State changes are appended to a log in the same record format. From time to time the
+/// whole state is written to a temporary file and renamed into place as a snapshot, after
+/// which the log is emptied. The state is loaded by replaying the log onto the snapshot.
+///
+/// When the files are opened, a partially written trailing record is detected and
+/// truncated, while a corrupt record header, or a corrupt record that fits in the file, is
+/// reported as an error. The block index is rebuilt if it does not match the block file.
+pub struct FileBackend {
+    /// The directory holding the storage files
+    dir: PathBuf,
+    /// The block file, opened for appending
+    blocks_file: File,
+    /// The block index file, opened for appending
+    index_file: File,
+    /// Length in bytes of the complete records in the block file
+    blocks_len: u64,
+    /// Number of blocks in the block file
+    block_count: u64,
+    /// The state log, opened for appending
+    state_log: File,
+    /// Length in bytes of the complete records in the state log
+    state_log_len: u64,
+    /// Number of changes in the state log
+    state_log_count: usize,
+}
+
+impl FileBackend {
+    /// Opens the storage files in `dir`, creating the directory if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The data directory.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<(FileBackend, Vec<Block>)>` - The backend and every complete block
+    ///   found in the block file, in the order they were written.
+    pub fn open<P: AsRef<Path>>(dir: P) -> IcnResult<(Self, Vec<Block>)> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .map_err(|e| IcnError::Storage(format!("Failed to create data directory {}: {}", dir.display(), e)))?;
+
+        let (blocks_file, records, blocks_len) = Self::open_record_file(&dir.join(BLOCKS_FILE))?;
+        let mut blocks = Vec::with_capacity(records.len());
+        let mut index = Vec::with_capacity(records.len() * INDEX_ENTRY_SIZE as usize);
+        for (offset, payload) in records {
+            let block = serde_json::from_slice(&payload)
+                .map_err(|e| IcnError::Storage(format!("Failed to deserialize stored block: {}", e)))?;
+            blocks.push(block);
+            index.extend_from_slice(&offset.to_be_bytes());
+        }
+
+        let index_path = dir.join(BLOCK_INDEX_FILE);
+        let mut index_file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&index_path)
+            .map_err(|e| IcnError::Storage(format!("Failed to open {}: {}", index_path.display(), e)))?;
+        let mut stored_index = Vec::new();
+        index_file.read_to_end(&mut stored_index)
+            .map_err(|e| IcnError::Storage(format!("Failed to read {}: {}", index_path.display(), e)))?;
+        if stored_index != index {
+            log::warn!("Rebuilding block index {}", index_path.display());
+            index_file.set_len(0)
+                .and_then(|_| index_file.write_all(&index))
+                .and_then(|_| index_file.sync_data())
+                .map_err(|e| IcnError::Storage(format!("Failed to rebuild {}: {}", index_path.display(), e)))?;
+        }
+
+        let (state_log, changes, state_log_len) = Self::open_record_file(&dir.join(STATE_LOG_FILE))?;
+
+        let backend = FileBackend {
+            dir,
+            blocks_file,
+            index_file,
+            blocks_len,
+            block_count: blocks.len() as u64,
+            state_log,
+            state_log_len,
+            state_log_count: changes.len(),
+        };
+        Ok((backend, blocks))
+    }
+
+    /// Opens a record file for appending and reads its complete records.
+    ///
+    /// An incomplete trailing record left by an interrupted write is truncated. Returns the
+    /// file, the offset and payload of every record, and the length of the records.
+    fn open_record_file(path: &Path) -> IcnResult<OpenedRecordFile> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| IcnError::Storage(format!("Failed to open {}: {}", path.display(), e)))?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
+            .map_err(|e| IcnError::Storage(format!("Failed to read {}: {}", path.display(), e)))?;
+        let (records, valid_len) = Self::read_records(&contents, path)?;
+        if valid_len < contents.len() {
+            log::warn!(
+                "Truncating {} bytes of incomplete data in {}",
+                contents.len() - valid_len,
+                path.display()
+            );
+            file.set_len(valid_len as u64)
+                .map_err(|e| IcnError::Storage(format!("Failed to truncate {}: {}", path.display(), e)))?;
+        }
+
+        let records = records.into_iter()
+            .map(|(offset, payload)| (offset as u64, payload.to_vec()))
+            .collect();
+        Ok((file, records, valid_len as u64))
+    }
+
+    /// Parses records, stopping at an incomplete trailing record.
+    ///
+    /// Returns the offset and payload of every complete record along with the length of
+    /// the data they were read from. Only a record with a valid header that runs past the
+    /// end of the file is treated as an interrupted write. A header that fails its
+    /// checksum, or a record that fits in the file but fails its payload checksum, is
+    /// corruption, so it is an error.
+    fn read_records<'a>(contents: &'a [u8], path: &Path) -> IcnResult<(Vec<Record<'a>>, usize)> {
+        let corrupt = |offset| IcnError::Storage(format!("Corrupt record at offset {} of {}", offset, path.display()));
+
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while contents.len() - offset >= RECORD_HEADER_SIZE {
+            let (length, checksum) = Self::decode_header(&contents[offset..offset + RECORD_HEADER_SIZE])
+                .ok_or_else(|| corrupt(offset))?;
+
+            let start = offset + RECORD_HEADER_SIZE;
+            let Some(payload) = contents.get(start..start + length) else { break };
+            if Sha256::digest(payload).as_slice() != checksum {
+                return Err(corrupt(offset));
+            }
+            records.push((offset, payload));
+            offset = start + length;
+        }
+        Ok((records, offset))
+    }
+
+    /// Checks a record header against its header checksum.
+    ///
+    /// Returns the payload length and payload checksum, or `None` if the header is corrupt.
+    fn decode_header(header: &[u8]) -> Option<(usize, &[u8])> {
+        let (body, header_checksum) = header.split_at(RECORD_HEADER_BODY_SIZE);
+        if &Sha256::digest(body)[..4] != header_checksum {
+            return None;
+        }
+        let mut length = [0u8; 4];
+        length.copy_from_slice(&body[..4]);
+        Some((u32::from_be_bytes(length) as usize, &body[4..]))
+    }
+
+    /// Returns the number of blocks in the block file.
+    pub fn block_count(&self) -> u64 {
+        self.block_count
+    }
+
+    /// Reads the block at the given height through the block index.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The position of the block in the block file, starting at zero.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Option<Block>>` - The block, `None` if there is no block at that
+    ///   height, or an `IcnError` if the files cannot be read or the record is corrupt.
+    pub fn read_block(&self, height: u64) -> IcnResult<Option<Block>> {
+        if height >= self.block_count {
+            return Ok(None);
+        }
+
+        let mut offset = [0u8; INDEX_ENTRY_SIZE as usize];
+        let mut index_file = &self.index_file;
+        index_file.seek(SeekFrom::Start(height * INDEX_ENTRY_SIZE))
+            .and_then(|_| index_file.read_exact(&mut offset))
+            .map_err(|e| IcnError::Storage(format!("Failed to read block index: {}", e)))?;
+        let offset = u64::from_be_bytes(offset);
+
+        let mut header = [0u8; RECORD_HEADER_SIZE];
+        let mut blocks_file = &self.blocks_file;
+        blocks_file.seek(SeekFrom::Start(offset))
+            .and_then(|_| blocks_file.read_exact(&mut header))
+            .map_err(|e| IcnError::Storage(format!("Failed to read block record: {}", e)))?;
+        let corrupt = || IcnError::Storage(format!("Corrupt block record at offset {}", offset));
+        let (length, checksum) = Self::decode_header(&header).ok_or_else(corrupt)?;
+        let mut payload = vec![0u8; length];
+        blocks_file.read_exact(&mut payload)
+            .map_err(|e| IcnError::Storage(format!("Failed to read block record: {}", e)))?;
+        if Sha256::digest(&payload).as_slice() != checksum {
+            return Err(corrupt());
+        }
+
+        serde_json::from_slice(&payload)
+            .map(Some)
+            .map_err(|e| IcnError::Storage(format!("Failed to deserialize stored block: {}", e)))
+    }
+
+    /// Appends a block to the block file and flushes it to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - The block to append.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` once the block and its index entry are durably
+    ///   written. On error, anything partially written is removed again.
+    pub fn append_block(&mut self, block: &Block) -> IcnResult<()> {
+        let payload = serde_json::to_vec(block)
+            .map_err(|e| IcnError::Storage(format!("Failed to serialize block: {}", e)))?;
+        let record = Self::encode_record(&payload)?;
+
+        let offset = self.blocks_len;
+        let result = self.blocks_file.write_all(&record)
+            .and_then(|_| self.blocks_file.sync_data())
+            .and_then(|_| self.index_file.write_all(&offset.to_be_bytes()))
+            .and_then(|_| self.index_file.sync_data());
+        if let Err(e) = result {
+            // Best effort; anything left behind is truncated or rebuilt when the files are reopened
+            let _ = self.blocks_file.set_len(offset);
+            let _ = self.index_file.set_len(self.block_count * INDEX_ENTRY_SIZE);
+            return Err(IcnError::Storage(format!("Failed to write block: {}", e)));
+        }
+
+        self.blocks_len += record.len() as u64;
+        self.block_count += 1;
+        Ok(())
+    }
+
+    /// Appends a state change to the state log and flushes it to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The change to log.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` once the change is durably written. On error,
+    ///   anything partially written is removed again.
+    pub fn log_state_change(&mut self, change: &StateChange) -> IcnResult<()> {
+        let payload = serde_json::to_vec(change)
+            .map_err(|e| IcnError::Storage(format!("Failed to serialize state change: {}", e)))?;
+        let record = Self::encode_record(&payload)?;
+
+        if let Err(e) = self.state_log.write_all(&record).and_then(|_| self.state_log.sync_data()) {
+            // Best effort; anything left behind is truncated when the log is reopened
+            let _ = self.state_log.set_len(self.state_log_len);
+            return Err(IcnError::Storage(format!("Failed to write state change: {}", e)));
+        }
+
+        self.state_log_len += record.len() as u64;
+        self.state_log_count += 1;
+        Ok(())
+    }
+
+    /// Returns whether enough changes have been logged that the state should be saved as
+    /// a new snapshot.
+    pub fn state_log_is_full(&self) -> bool {
+        self.state_log_count >= STATE_LOG_COMPACTION_THRESHOLD
+    }
+
+    /// Frames a payload as a record: its length, its checksum, a checksum of those two
+    /// and the payload itself.
+    fn encode_record(payload: &[u8]) -> IcnResult<Vec<u8>> {
+        let length = u32::try_from(payload.len())
+            .map_err(|_| IcnError::Storage("Record is too large to store".to_string()))?;
+
+        let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+        record.extend_from_slice(&length.to_be_bytes());
+        record.extend_from_slice(&Sha256::digest(payload));
+        let header_checksum = Sha256::digest(&record[..RECORD_HEADER_BODY_SIZE]);
+        record.extend_from_slice(&header_checksum[..4]);
+        record.extend_from_slice(payload);
+        Ok(record)
+    }
+
+    /// Loads the state from the latest snapshot and the changes logged since.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<StateStorage>` - The saved state, empty if nothing has been saved.
+    pub fn load_state(&self) -> IcnResult<StateStorage> {
+        let path = self.dir.join(STATE_FILE);
+        let mut state = if path.exists() {
+            let contents = fs::read(&path)
+                .map_err(|e| IcnError::Storage(format!("Failed to read {}: {}", path.display(), e)))?;
+            serde_json::from_slice(&contents)
+                .map_err(|e| IcnError::Storage(format!("Failed to deserialize state: {}", e)))?
+        } else {
+            StateStorage::new()
+        };
+
+        let log_path = self.dir.join(STATE_LOG_FILE);
+        let contents = fs::read(&log_path)
+            .map_err(|e| IcnError::Storage(format!("Failed to read {}: {}", log_path.display(), e)))?;
+        let (records, _) = Self::read_records(&contents, &log_path)?;
+        for (_, payload) in records {
+            let change: StateChange = serde_json::from_slice(payload)
+                .map_err(|e| IcnError::Storage(format!("Failed to deserialize state change: {}", e)))?;
+            state.apply(&change)?;
+        }
+        Ok(state)
+    }
+
+    /// Atomically replaces the state snapshot and empties the state log.
+    ///
+    /// If the log cannot be emptied after the snapshot is in place, replaying it onto the
+    /// new snapshot yields the same state, so nothing is lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to save.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` once the new snapshot is in place and the log is empty.
+    pub fn save_state(&mut self, state: &StateStorage) -> IcnResult<()> {
+        let contents = serde_json::to_vec(state)
+            .map_err(|e| IcnError::Storage(format!("Failed to serialize state: {}", e)))?;
+        let temp_path = self.dir.join(STATE_TEMP_FILE);
+        let mut temp_file = File::create(&temp_path)
+            .map_err(|e| IcnError::Storage(format!("Failed to create {}: {}", temp_path.display(), e)))?;
+        temp_file.write_all(&contents)
+            .and_then(|_| temp_file.sync_all())
+            .map_err(|e| IcnError::Storage(format!("Failed to write {}: {}", temp_path.display(), e)))?;
+        fs::rename(&temp_path, self.dir.join(STATE_FILE))
+            .map_err(|e| IcnError::Storage(format!("Failed to replace state snapshot: {}", e)))?;
+        // The rename must be durable before the log is emptied, or a crash could leave the
+        // old snapshot next to an empty log
+        File::open(&self.dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| IcnError::Storage(format!("Failed to sync {}: {}", self.dir.display(), e)))?;
+
+        self.state_log.set_len(0)
+            .and_then(|_| self.state_log.sync_data())
+            .map_err(|e| IcnError::Storage(format!("Failed to empty state log: {}", e)))?;
+        self.state_log_len = 0;
+        self.state_log_count = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_blocks(count: u64) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for index in 0..count {
+            let previous_hash = blocks.last().map(|block| block.hash.clone()).unwrap_or_else(|| "genesis".to_string());
+            blocks.push(Block::new(index, vec![format!("tx{}", index)], previous_hash, "proposer".to_string()));
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_blocks_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocks = create_test_blocks(3);
+
+        let (mut backend, loaded) = FileBackend::open(dir.path()).unwrap();
+        assert!(loaded.is_empty());
+        for block in &blocks {
+            backend.append_block(block).unwrap();
+        }
+        drop(backend);
+
+        let (_, loaded) = FileBackend::open(dir.path()).unwrap();
+        assert_eq!(loaded, blocks);
+    }
+
+    #[test]
+    fn test_truncated_record_is_recovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocks = create_test_blocks(4);
+
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        for block in &blocks {
+            backend.append_block(block).unwrap();
+        }
+        drop(backend);
+
+        // Simulate a crash part way through writing the last record
+        let blocks_path = dir.path().join(BLOCKS_FILE);
+        let length = fs::metadata(&blocks_path).unwrap().len();
+        OpenOptions::new().write(true).open(&blocks_path).unwrap().set_len(length - 10).unwrap();
+
+        let (mut backend, loaded) = FileBackend::open(dir.path()).unwrap();
+        assert_eq!(loaded, blocks[..3]);
+
+        // New blocks are appended after the last complete record
+        backend.append_block(&blocks[3]).unwrap();
+        drop(backend);
+        let (_, loaded) = FileBackend::open(dir.path()).unwrap();
+        assert_eq!(loaded, blocks);
+    }
+
+    #[test]
+    fn test_corrupt_record_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        for block in &create_test_blocks(3) {
+            backend.append_block(block).unwrap();
+        }
+        drop(backend);
+
+        // Flip a byte in the payload of the first record
+        let blocks_path = dir.path().join(BLOCKS_FILE);
+        let mut contents = fs::read(&blocks_path).unwrap();
+        contents[RECORD_HEADER_SIZE + 1] ^= 0xff;
+        fs::write(&blocks_path, &contents).unwrap();
+
+        match FileBackend::open(dir.path()) {
+            Err(IcnError::Storage(msg)) => assert!(msg.contains("Corrupt record at offset 0")),
+            other => panic!("Expected corrupt record error, got {:?}", other.map(|(_, blocks)| blocks)),
+        }
+        assert_eq!(fs::metadata(&blocks_path).unwrap().len(), contents.len() as u64);
+    }
+
+    #[test]
+    fn test_corrupt_record_length_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        for block in &create_test_blocks(3) {
+            backend.append_block(block).unwrap();
+        }
+        drop(backend);
+
+        // Make the first record claim to run past the end of the file
+        let blocks_path = dir.path().join(BLOCKS_FILE);
+        let mut contents = fs::read(&blocks_path).unwrap();
+        contents[0] ^= 0x40;
+        fs::write(&blocks_path, &contents).unwrap();
+
+        match FileBackend::open(dir.path()) {
+            Err(IcnError::Storage(msg)) => assert!(msg.contains("Corrupt record at offset 0")),
+            other => panic!("Expected corrupt record error, got {:?}", other.map(|(_, blocks)| blocks)),
+        }
+        assert_eq!(fs::metadata(&blocks_path).unwrap().len(), contents.len() as u64);
+    }
+
+    #[test]
+    fn test_blocks_are_read_through_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocks = create_test_blocks(3);
+
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        for block in &blocks {
+            backend.append_block(block).unwrap();
+        }
+        assert_eq!(backend.block_count(), 3);
+        assert_eq!(backend.read_block(1).unwrap(), Some(blocks[1].clone()));
+        assert_eq!(backend.read_block(3).unwrap(), None);
+        drop(backend);
+
+        // A missing or stale index is rebuilt from the block file
+        let index_path = dir.path().join(BLOCK_INDEX_FILE);
+        OpenOptions::new().write(true).open(&index_path).unwrap().set_len(INDEX_ENTRY_SIZE).unwrap();
+        let (backend, _) = FileBackend::open(dir.path()).unwrap();
+        assert_eq!(fs::metadata(&index_path).unwrap().len(), 3 * INDEX_ENTRY_SIZE);
+        assert_eq!(backend.read_block(2).unwrap(), Some(blocks[2].clone()));
+    }
+
+    #[test]
+    fn test_state_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        assert_eq!(backend.load_state().unwrap().state_count(), 0);
+
+        let mut state = StateStorage::new();
+        state.update_state("key1", "value1").unwrap();
+        backend.save_state(&state).unwrap();
+        state.update_state("key2", "value2").unwrap();
+        state.update_state_ns("contract1", "key1", "value3").unwrap();
+        backend.save_state(&state).unwrap();
+
+        let loaded = backend.load_state().unwrap();
+        assert_eq!(loaded.state_count(), 2);
+        assert_eq!(loaded.get_state("key2"), Some("value2".to_string()));
+        assert_eq!(loaded.get_state_ns("contract1", "key1"), Some("value3".to_string()));
+        assert!(!dir.path().join(STATE_TEMP_FILE).exists());
+    }
+
+    #[test]
+    fn test_state_log_is_replayed_onto_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let set = |key: &str, value: &str| StateChange::Set { key: key.to_string(), value: value.to_string() };
+
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        let mut state = StateStorage::new();
+        state.apply(&set("key1", "value1")).unwrap();
+        backend.save_state(&state).unwrap();
+        backend.log_state_change(&set("key1", "value2")).unwrap();
+        backend.log_state_change(&set("key2", "value3")).unwrap();
+        drop(backend);
+
+        // Simulate a crash part way through logging a change
+        let log_path = dir.path().join(STATE_LOG_FILE);
+        let length = fs::metadata(&log_path).unwrap().len();
+        OpenOptions::new().write(true).open(&log_path).unwrap().set_len(length - 5).unwrap();
+
+        let (mut backend, _) = FileBackend::open(dir.path()).unwrap();
+        let loaded = backend.load_state().unwrap();
+        assert_eq!(loaded.get_state("key1"), Some("value2".to_string()));
+        assert_eq!(loaded.get_state("key2"), None);
+        assert!(!backend.state_log_is_full());
+
+        // Compaction empties the log without changing the loaded state
+        backend.save_state(&loaded).unwrap();
+        assert_eq!(fs::metadata(&log_path).unwrap().len(), 0);
+        assert_eq!(backend.load_state().unwrap().get_state("key1"), Some("value2".to_string()));
+    }
+}
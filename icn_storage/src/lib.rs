@@ -7,14 +7,17 @@
 //! `Arc` and `RwLock`. The `Storage` struct serves as the main entry point for
 //! all storage-related operations in the ICN node.
 
-use std::sync::{Arc, RwLock};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
 use icn_shared::{Block, IcnResult, IcnError};
 
 pub mod block_storage;
+pub mod file_backend;
 pub mod state_storage;
 
 use block_storage::BlockStorage;
-use state_storage::StateStorage;
+use file_backend::FileBackend;
+use state_storage::{StateChange, StateStorage};
 
 /// `Storage` is the central structure that manages block and state storage for the ICN node.
 /// 
@@ -25,6 +28,8 @@ pub struct Storage {
     block_storage: Arc<RwLock<BlockStorage>>,
     /// Thread-safe access to state storage
     state_storage: Arc<RwLock<StateStorage>>,
+    /// Backend persisting blocks and state to disk, if the storage is file-backed
+    file_backend: Option<Arc<Mutex<FileBackend>>>,
 }
 
 impl Storage {
@@ -39,9 +44,46 @@ impl Storage {
         Storage {
             block_storage: Arc::new(RwLock::new(BlockStorage::new())),
             state_storage: Arc::new(RwLock::new(StateStorage::new())),
+            file_backend: None,
         }
     }
 
+    /// Creates a new instance of `Storage` persisted under the given data directory.
+    ///
+    /// Blocks and state already saved in the directory are loaded. Any partially written
+    /// block left by a crash is discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The data directory.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Storage>` - The file-backed storage, or an `IcnError` if the directory cannot be read.
+    pub fn new_with_path<P: AsRef<Path>>(path: P) -> IcnResult<Self> {
+        let (file_backend, blocks) = FileBackend::open(path)?;
+
+        let mut block_storage = BlockStorage::new();
+        for block in blocks {
+            block_storage.store_block(block)?;
+        }
+        let state_storage = file_backend.load_state()?;
+
+        Ok(Storage {
+            block_storage: Arc::new(RwLock::new(block_storage)),
+            state_storage: Arc::new(RwLock::new(state_storage)),
+            file_backend: Some(Arc::new(Mutex::new(file_backend))),
+        })
+    }
+
+    /// Acquires the file backend, if the storage is file-backed.
+    fn lock_file_backend(&self) -> IcnResult<Option<std::sync::MutexGuard<'_, FileBackend>>> {
+        self.file_backend.as_ref()
+            .map(|backend| backend.lock()
+                .map_err(|_| IcnError::Storage("Failed to acquire lock for file backend".to_string())))
+            .transpose()
+    }
+
     /// Adds a block to the block storage.
     ///
     /// This method acquires a write lock on the block storage before adding the block.
@@ -56,6 +98,12 @@ impl Storage {
     pub fn add_block(&self, block: Block) -> IcnResult<()> {
         let mut storage = self.block_storage.write()
             .map_err(|_| IcnError::Storage("Failed to acquire write lock for block storage".to_string()))?;
+        if let Some(mut backend) = self.lock_file_backend()? {
+            if storage.block_exists(&block.hash) {
+                return Err(IcnError::Storage("Block with this hash already exists".to_string()));
+            }
+            backend.append_block(&block)?;
+        }
         storage.store_block(block)
     }
 
@@ -89,9 +137,7 @@ impl Storage {
     ///
     /// * `IcnResult<()>` - Returns `Ok(())` if the state is successfully updated, or an `IcnError` otherwise.
     pub fn update_state(&self, key: &str, value: &str) -> IcnResult<()> {
-        let mut storage = self.state_storage.write()
-            .map_err(|_| IcnError::Storage("Failed to acquire write lock for state storage".to_string()))?;
-        storage.update_state(key, value)
+        self.apply_state_change(StateChange::Set { key: key.to_string(), value: value.to_string() })
     }
 
     /// Retrieves a state from the state storage.
@@ -111,6 +157,93 @@ impl Storage {
         Ok(storage.get_state(key))
     }
 
+    /// Updates a state within a namespace in the state storage.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the key, such as a contract id.
+    /// * `key` - The key of the state to update.
+    /// * `value` - The value to set for the key.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the state is successfully updated, or an `IcnError` otherwise.
+    pub fn update_state_ns(&self, namespace: &str, key: &str, value: &str) -> IcnResult<()> {
+        self.apply_state_change(StateChange::SetNs {
+            namespace: namespace.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Retrieves a state within a namespace from the state storage.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the key.
+    /// * `key` - The key of the state to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Option<String>>` - Returns the state value if found, or `None` if not found, or an `IcnError` if lock acquisition fails.
+    pub fn get_state_ns(&self, namespace: &str, key: &str) -> IcnResult<Option<String>> {
+        let storage = self.state_storage.read()
+            .map_err(|_| IcnError::Storage("Failed to acquire read lock for state storage".to_string()))?;
+        Ok(storage.get_state_ns(namespace, key))
+    }
+
+    /// Lists every state in a namespace, ordered by key.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to list.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<Vec<(String, String)>>` - Returns the key-value pairs, or an `IcnError` if lock acquisition fails.
+    pub fn iterate_prefix(&self, namespace: &str) -> IcnResult<Vec<(String, String)>> {
+        let storage = self.state_storage.read()
+            .map_err(|_| IcnError::Storage("Failed to acquire read lock for state storage".to_string()))?;
+        Ok(storage.iterate_prefix(namespace))
+    }
+
+    /// Removes a namespace and all of its states from the state storage.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the namespace is removed, or an `IcnError` otherwise.
+    pub fn delete_namespace(&self, namespace: &str) -> IcnResult<()> {
+        self.apply_state_change(StateChange::DeleteNamespace { namespace: namespace.to_string() })
+    }
+
+    /// Applies a change to the state storage, logging it to disk first if the storage is
+    /// file-backed.
+    ///
+    /// The in-memory state is only changed once the change is durable, so a failed write
+    /// leaves memory and disk in agreement. When the log is full the state is compacted
+    /// into a new snapshot; a failed compaction is retried after the next change.
+    fn apply_state_change(&self, change: StateChange) -> IcnResult<()> {
+        let mut storage = self.state_storage.write()
+            .map_err(|_| IcnError::Storage("Failed to acquire write lock for state storage".to_string()))?;
+        change.validate()?;
+
+        if let Some(mut backend) = self.lock_file_backend()? {
+            backend.log_state_change(&change)?;
+            storage.apply(&change)?;
+            if backend.state_log_is_full() {
+                if let Err(e) = backend.save_state(&storage) {
+                    log::warn!("Failed to compact state log: {}", e);
+                }
+            }
+            return Ok(());
+        }
+        storage.apply(&change)
+    }
+
     /// Verifies the integrity of a block in the block storage.
     ///
     /// This method acquires a read lock on the block storage before verifying the block's integrity.
@@ -162,4 +295,43 @@ mod tests {
         assert!(storage.add_block(block).is_ok());
         assert!(storage.verify_block_integrity(&block_hash).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_file_backed_storage_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let block = Block::new(0, vec![], "genesis".to_string(), "proposer".to_string());
+        let block_hash = block.hash.clone();
+
+        {
+            let storage = Storage::new_with_path(dir.path()).unwrap();
+            storage.add_block(block.clone()).unwrap();
+            assert!(storage.add_block(block.clone()).is_err());
+            storage.update_state("key1", "value1").unwrap();
+        }
+
+        let storage = Storage::new_with_path(dir.path()).unwrap();
+        assert_eq!(storage.get_block(&block_hash).unwrap(), Some(block));
+        assert!(storage.verify_block_integrity(&block_hash).unwrap());
+        assert_eq!(storage.get_state("key1").unwrap(), Some("value1".to_string()));
+    }
+
+    #[test]
+    fn test_namespaced_state_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let storage = Storage::new_with_path(dir.path()).unwrap();
+            storage.update_state_ns("contract1", "counter", "1").unwrap();
+            storage.update_state_ns("contract2", "counter", "2").unwrap();
+            storage.delete_namespace("contract2").unwrap();
+        }
+
+        let storage = Storage::new_with_path(dir.path()).unwrap();
+        assert_eq!(storage.iterate_prefix("contract1").unwrap(), vec![("counter".to_string(), "1".to_string())]);
+        assert_eq!(storage.get_state_ns("contract2", "counter").unwrap(), None);
+
+        // An invalid change is rejected before it reaches the log
+        assert!(storage.update_state_ns("", "counter", "3").is_err());
+        drop(storage);
+        assert!(Storage::new_with_path(dir.path()).is_ok());
+    }
+}
@@ -1,12 +1,51 @@
 // file: icn_storage/src/state_storage.rs
 
-use std::collections::HashMap;
-use icn_shared::IcnResult;
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use icn_shared::{IcnError, IcnResult};
 
 /// The `StateStorage` struct is responsible for managing the state of the blockchain.
-/// It uses an in-memory `HashMap` to store key-value pairs representing the state.
+/// It uses an in-memory `HashMap` to store key-value pairs representing the state,
+/// plus separate ordered maps for namespaced state such as the keys of one contract.
+#[derive(Serialize, Deserialize)]
 pub struct StateStorage {
     storage: HashMap<String, String>,
+    #[serde(default)]
+    namespaces: HashMap<String, BTreeMap<String, String>>,
+}
+
+/// A single change to the state, as recorded in the state log of file-backed storage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StateChange {
+    /// Sets a key outside any namespace
+    Set { key: String, value: String },
+    /// Sets a key within a namespace
+    SetNs { namespace: String, key: String, value: String },
+    /// Removes a namespace and all of its keys
+    DeleteNamespace { namespace: String },
+}
+
+impl StateChange {
+    /// Checks that the change can be applied.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if applying the change cannot fail, or an error if
+    ///   the namespace is empty.
+    pub fn validate(&self) -> IcnResult<()> {
+        match self {
+            StateChange::Set { .. } | StateChange::DeleteNamespace { .. } => Ok(()),
+            StateChange::SetNs { namespace, .. } => check_namespace(namespace),
+        }
+    }
+}
+
+/// Rejects the empty namespace, which is reserved for keys stored through `update_state`.
+fn check_namespace(namespace: &str) -> IcnResult<()> {
+    if namespace.is_empty() {
+        return Err(IcnError::Storage("Namespace cannot be empty".to_string()));
+    }
+    Ok(())
 }
 
 impl StateStorage {
@@ -14,6 +53,7 @@ impl StateStorage {
     pub fn new() -> Self {
         StateStorage {
             storage: HashMap::new(),
+            namespaces: HashMap::new(),
         }
     }
 
@@ -82,15 +122,102 @@ impl StateStorage {
         self.storage.len()
     }
 
-    /// Clears all key-value pairs from the state storage.
+    /// Clears all key-value pairs, including namespaced ones, from the state storage.
     ///
     /// # Returns
     ///
     /// * `IcnResult<()>` - Returns `Ok(())` if the storage is successfully cleared.
     pub fn clear_state(&mut self) -> IcnResult<()> {
         self.storage.clear();
+        self.namespaces.clear();
         Ok(())
     }
+
+    /// Updates a key-value pair within a namespace.
+    ///
+    /// Keys in a namespace never collide with keys in other namespaces or with
+    /// keys stored through `update_state`.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the key, such as a contract id.
+    /// * `key` - A string slice that holds the key.
+    /// * `value` - A string slice that holds the value.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the state is updated, or an error if the namespace is empty.
+    pub fn update_state_ns(&mut self, namespace: &str, key: &str, value: &str) -> IcnResult<()> {
+        check_namespace(namespace)?;
+        self.namespaces
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Retrieves a value within a namespace by its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the key.
+    /// * `key` - A string slice that holds the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value associated with the key, or `None` if it does not exist.
+    pub fn get_state_ns(&self, namespace: &str, key: &str) -> Option<String> {
+        self.namespaces.get(namespace)?.get(key).cloned()
+    }
+
+    /// Returns every key-value pair in a namespace, ordered by key.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to list.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, String)>` - The key-value pairs, empty if the namespace does not exist.
+    pub fn iterate_prefix(&self, namespace: &str) -> Vec<(String, String)> {
+        self.namespaces.get(namespace)
+            .map(|entries| entries.iter().map(|(key, value)| (key.clone(), value.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes a namespace and all of its key-value pairs.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the namespace is removed or didn't exist.
+    pub fn delete_namespace(&mut self, namespace: &str) -> IcnResult<()> {
+        self.namespaces.remove(namespace);
+        Ok(())
+    }
+
+    /// Applies a state change.
+    ///
+    /// Every change leaves a key with the same value however often it is applied, so
+    /// replaying changes that are already reflected in the state has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The change to apply.
+    ///
+    /// # Returns
+    ///
+    /// * `IcnResult<()>` - Returns `Ok(())` if the change is applied, or an error if it is invalid.
+    pub fn apply(&mut self, change: &StateChange) -> IcnResult<()> {
+        match change {
+            StateChange::Set { key, value } => self.update_state(key, value),
+            StateChange::SetNs { namespace, key, value } => self.update_state_ns(namespace, key, value),
+            StateChange::DeleteNamespace { namespace } => self.delete_namespace(namespace),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -130,4 +257,61 @@ mod tests {
         assert!(storage.clear_state().is_ok());
         assert_eq!(storage.state_count(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_namespace_iteration_order() {
+        let mut storage = StateStorage::new();
+        storage.update_state_ns("contract1", "b", "2").unwrap();
+        storage.update_state_ns("contract1", "c", "3").unwrap();
+        storage.update_state_ns("contract1", "a", "1").unwrap();
+
+        let entries = storage.iterate_prefix("contract1");
+        let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b", "c"]);
+        assert!(storage.iterate_prefix("contract2").is_empty());
+        assert!(storage.update_state_ns("", "a", "1").is_err());
+    }
+
+    #[test]
+    fn test_namespace_isolation_and_deletion() {
+        let mut storage = StateStorage::new();
+        storage.update_state("key1", "flat").unwrap();
+        storage.update_state_ns("contract1", "key1", "first").unwrap();
+        storage.update_state_ns("contract2", "key1", "second").unwrap();
+
+        assert_eq!(storage.get_state("key1"), Some("flat".to_string()));
+        assert_eq!(storage.get_state_ns("contract1", "key1"), Some("first".to_string()));
+        assert_eq!(storage.get_state_ns("contract2", "key1"), Some("second".to_string()));
+
+        storage.delete_namespace("contract1").unwrap();
+        assert_eq!(storage.get_state_ns("contract1", "key1"), None);
+        assert_eq!(storage.get_state_ns("contract2", "key1"), Some("second".to_string()));
+        assert_eq!(storage.get_state("key1"), Some("flat".to_string()));
+        assert_eq!(storage.state_count(), 1);
+    }
+
+    #[test]
+    fn test_apply_state_changes() {
+        let changes = vec![
+            StateChange::Set { key: "key1".to_string(), value: "value1".to_string() },
+            StateChange::SetNs { namespace: "contract1".to_string(), key: "a".to_string(), value: "1".to_string() },
+            StateChange::DeleteNamespace { namespace: "contract1".to_string() },
+            StateChange::SetNs { namespace: "contract1".to_string(), key: "b".to_string(), value: "2".to_string() },
+        ];
+
+        let mut storage = StateStorage::new();
+        for change in &changes {
+            storage.apply(change).unwrap();
+        }
+        // Replaying the same changes leaves the state unchanged
+        for change in &changes {
+            storage.apply(change).unwrap();
+        }
+        assert_eq!(storage.get_state("key1"), Some("value1".to_string()));
+        assert_eq!(storage.iterate_prefix("contract1"), vec![("b".to_string(), "2".to_string())]);
+
+        let invalid = StateChange::SetNs { namespace: String::new(), key: "a".to_string(), value: "1".to_string() };
+        assert!(invalid.validate().is_err());
+        assert!(storage.apply(&invalid).is_err());
+    }
+}